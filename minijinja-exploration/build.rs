@@ -0,0 +1,70 @@
+//! Embeds `templates/` into the binary, see `src/embedded.rs`.
+//!
+//! Writes `$OUT_DIR/embedded_templates.rs` with one `(name, bytes)` entry
+//! per template.  With the `compress` feature the bytes are deflated first.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+fn main() -> io::Result<()> {
+    let root = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("templates");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut files = Vec::new();
+    if root.is_dir() {
+        collect(&root, "", &mut files)?;
+    }
+    files.sort();
+
+    let mut code = String::from("pub(crate) static TEMPLATES: &[(&str, &[u8])] = &[\n");
+    for (idx, (name, path)) in files.iter().enumerate() {
+        let path = embed(path, &out_dir, idx)?;
+        writeln!(
+            code,
+            "    ({name:?}, include_bytes!({:?})),",
+            path.display()
+        )
+        .unwrap();
+    }
+    code.push_str("];\n");
+    fs::write(out_dir.join("embedded_templates.rs"), code)
+}
+
+/// Adds every file below `dir` as `(name, path)`.  Hidden files and
+/// directories are skipped since the path loader refuses to load them.
+fn collect(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let name = format!("{prefix}{file_name}");
+        if entry.file_type()?.is_dir() {
+            collect(&entry.path(), &format!("{name}/"), files)?;
+        } else {
+            files.push((name, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "compress")]
+fn embed(path: &Path, out_dir: &Path, idx: usize) -> io::Result<PathBuf> {
+    let compressed = miniz_oxide::deflate::compress_to_vec(&fs::read(path)?, 9);
+    let target = out_dir.join(format!("template-{idx}.deflate"));
+    fs::write(&target, compressed)?;
+    Ok(target)
+}
+
+#[cfg(not(feature = "compress"))]
+fn embed(path: &Path, _out_dir: &Path, _idx: usize) -> io::Result<PathBuf> {
+    Ok(path.to_path_buf())
+}
@@ -0,0 +1,137 @@
+//! `dump`: shows how minijinja parses and compiles a template.
+//!
+//! This goes through `minijinja::machinery`, which is only public with the
+//! `unstable_machinery` features and may change between minijinja releases —
+//! which is exactly what makes the dumps useful for diffing.
+
+use super::{Args, CommandResult, UsageError};
+use minijinja::Error;
+use minijinja::machinery::{CodeGenerator, Instructions, ast};
+use serde_json::{Value as Json, json};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration dump [--format text|json] [--only ast|instructions] FILE
+       minijinja-exploration dump [--format text|json] [--only ast|instructions] --inline SOURCE
+
+Parses and compiles a template and prints its AST and the instructions of
+the template and of each of its blocks.";
+
+/// A parsed and compiled template.
+pub struct Compiled<'source> {
+    pub ast: ast::Stmt<'source>,
+    pub instructions: Instructions<'source>,
+    pub blocks: Vec<(&'source str, Instructions<'source>)>,
+}
+
+/// Parses and compiles `source` the way the default environment would.
+pub fn compile<'source>(
+    name: &'source str,
+    source: &'source str,
+) -> Result<Compiled<'source>, Error> {
+    let ast = super::parse(name, source)?;
+    let mut codegen = CodeGenerator::new(name, source);
+    codegen.compile_stmt(&ast);
+    let (instructions, blocks) = codegen.finish();
+    Ok(Compiled {
+        ast,
+        instructions,
+        blocks: blocks.into_iter().collect(),
+    })
+}
+
+fn instructions_json(instructions: &Instructions<'_>) -> Json {
+    (0..instructions.len() as u32)
+        .filter_map(|idx| {
+            let instr = instructions.get(idx)?;
+            Some(json!({
+                "index": idx,
+                "line": instructions.get_line(idx),
+                "instruction": instr,
+            }))
+        })
+        .collect()
+}
+
+fn write_listing(out: &mut String, instructions: &Instructions<'_>, indent: &str) {
+    let mut last_line = None;
+    for idx in 0..instructions.len() as u32 {
+        let Some(instr) = instructions.get(idx) else {
+            break;
+        };
+        let line = instructions.get_line(idx);
+        let _ = write!(out, "{indent}{idx:05} | {instr:?}");
+        if line != last_line {
+            if let Some(line) = line {
+                let _ = write!(out, "  [line {line}]");
+            }
+            last_line = line;
+        }
+        out.push('\n');
+    }
+}
+
+impl Compiled<'_> {
+    /// The dump as JSON, restricted to `only` if given.
+    pub fn to_json(&self, only: Option<&str>) -> Json {
+        let mut rv = json!({});
+        if only != Some("instructions") {
+            rv["ast"] = serde_json::to_value(&self.ast).unwrap_or(Json::Null);
+        }
+        if only != Some("ast") {
+            rv["instructions"] = instructions_json(&self.instructions);
+            rv["blocks"] = self
+                .blocks
+                .iter()
+                .map(|(name, instructions)| (name.to_string(), instructions_json(instructions)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        rv
+    }
+
+    /// The dump as a human readable listing, restricted to `only` if given.
+    pub fn to_listing(&self, only: Option<&str>) -> String {
+        let mut out = String::new();
+        if only != Some("instructions") {
+            let _ = writeln!(out, "ast:\n{:#?}\n", self.ast);
+        }
+        if only != Some("ast") {
+            out.push_str("instructions:\n");
+            write_listing(&mut out, &self.instructions, "  ");
+            for (name, instructions) in &self.blocks {
+                let _ = writeln!(out, "\nblock {name:?}:");
+                write_listing(&mut out, instructions, "  ");
+            }
+        }
+        out
+    }
+}
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["format", "only", "inline"], &[])?;
+    let only = args.value("only");
+    if !matches!(only, None | Some("ast" | "instructions")) {
+        return Err(UsageError("--only must be `ast` or `instructions`".into()).into());
+    }
+
+    let (name, source) = match (args.value("inline"), args.positional()) {
+        (Some(source), []) => ("<inline>".to_string(), source.to_string()),
+        (None, [path]) => (
+            path.clone(),
+            fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?,
+        ),
+        _ => return Err(UsageError("expected either FILE or --inline SOURCE".into()).into()),
+    };
+    let compiled = compile(&name, &source)?;
+
+    let output = match args.value("format").unwrap_or("text") {
+        "text" => compiled.to_listing(only),
+        "json" => serde_json::to_string_pretty(&compiled.to_json(only))? + "\n",
+        other => return Err(UsageError(format!("unknown format `{other}`")).into()),
+    };
+    io::Write::write_all(&mut io::stdout().lock(), output.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,320 @@
+//! `lint`: checks the variables used by templates against a context schema.
+//!
+//! The variables a template needs come from
+//! [`Template::undeclared_variables`](minijinja::Template::undeclared_variables)
+//! with nested lookups enabled, so `{{ user.name }}` is checked as
+//! `user.name`.  Names provided by the environment itself (`range`,
+//! `modify`, ...) are not reported.
+
+use super::{Args, CommandResult, UsageError};
+use minijinja::machinery::Token;
+use minijinja::{Environment, path_loader};
+use serde_json::Value as Json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration lint --templates DIR --schema FILE [--deny-unused]
+
+Checks every template below DIR against the variables declared in FILE and
+reports missing, misspelled and unused variables.  FILE is either a JSON
+Schema describing the context object or a manifest of the form
+`{\"variables\": [\"user.name\", \"items\"]}`.  Exits with status 1 if any
+errors are found; unused variables only fail the run with --deny-unused.";
+
+/// The context variables a template directory may use.
+///
+/// Each declared dotted path is either *open*, in which case any attribute
+/// below it may be accessed, or has declared children of its own.
+#[derive(Debug, Default)]
+pub struct Schema {
+    paths: BTreeMap<String, bool>,
+}
+
+impl Schema {
+    /// Reads a JSON Schema or a variable manifest.
+    pub fn from_json(json: &Json) -> Result<Schema, String> {
+        let mut schema = Schema::default();
+        if let Some(variables) = json.get("variables") {
+            let variables = variables
+                .as_array()
+                .ok_or("`variables` must be an array of strings")?;
+            for variable in variables {
+                let path = variable
+                    .as_str()
+                    .ok_or("`variables` must be an array of strings")?;
+                schema.declare(path);
+            }
+        } else if let Some(properties) = json.get("properties") {
+            schema.add_properties("", properties)?;
+        } else {
+            return Err("expected a JSON Schema with `properties` or a `variables` list".into());
+        }
+        Ok(schema)
+    }
+
+    fn add_properties(&mut self, prefix: &str, properties: &Json) -> Result<(), String> {
+        let properties = properties
+            .as_object()
+            .ok_or_else(|| format!("`properties` of `{prefix}` must be an object"))?;
+        for (name, property) in properties {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{prefix}.{name}")
+            };
+            match property.get("properties") {
+                Some(children) => {
+                    self.paths.insert(path.clone(), false);
+                    self.add_properties(&path, children)?;
+                }
+                None => self.declare(&path),
+            }
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, path: &str) {
+        // Every parent of a declared path is implicitly declared but closed.
+        let mut end = 0;
+        while let Some(dot) = path[end..].find('.') {
+            end += dot;
+            self.paths.entry(path[..end].to_string()).or_insert(false);
+            end += 1;
+        }
+        self.paths.insert(path.to_string(), true);
+    }
+
+    /// Whether a template may look up `used`.
+    pub fn allows(&self, used: &str) -> bool {
+        if self.paths.contains_key(used) {
+            return true;
+        }
+        self.paths
+            .iter()
+            .any(|(path, open)| *open && is_below(used, path))
+    }
+
+    /// The declared path closest to `used`, if it is close enough to be a
+    /// likely typo.
+    pub fn suggest(&self, used: &str) -> Option<&str> {
+        let max_distance = (used.len() / 3).clamp(1, 3);
+        self.paths
+            .keys()
+            .map(|path| (levenshtein(used, path), path))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()
+            .map(|(_, path)| path.as_str())
+    }
+}
+
+/// Whether `path` is `parent` itself or lies below it.
+fn is_below(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found by the linter.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    /// The template file, or the schema file for unused variables.
+    pub file: String,
+    /// 1-based line and column, if the problem has a location.
+    pub location: Option<(usize, usize)>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some((line, col)) = self.location {
+            write!(f, ":{line}:{col}")?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, ": {severity}: {}", self.message)
+    }
+}
+
+/// Lints every template below `dir` against `schema`.
+///
+/// Unused variables are reported against `schema_name`.
+pub fn lint_dir(dir: &Path, schema: &Schema, schema_name: &str) -> Result<Vec<Diagnostic>, String> {
+    let mut names = Vec::new();
+    collect_templates(dir, "", &mut names).map_err(|err| format!("{}: {err}", dir.display()))?;
+
+    let mut env = Environment::new();
+    crate::add_extensions(&mut env);
+    env.set_loader(path_loader(dir));
+    let globals: BTreeSet<String> = env.globals().map(|(name, _)| name.to_string()).collect();
+
+    let mut diagnostics = Vec::new();
+    let mut all_used = BTreeSet::new();
+    for name in &names {
+        let file = dir.join(name).display().to_string();
+        let tmpl = match env.get_template(name) {
+            Ok(tmpl) => tmpl,
+            Err(err) => {
+                diagnostics.push(Diagnostic {
+                    file: file.clone(),
+                    location: err.line().map(|line| (line, 1)),
+                    severity: Severity::Error,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+        let mut used: Vec<String> = tmpl
+            .undeclared_variables(true)
+            .into_iter()
+            .filter(|path| !globals.contains(root(path)))
+            .collect();
+        used.sort();
+        let first = diagnostics.len();
+        for path in used {
+            if !schema.allows(&path) {
+                let message = match schema.suggest(&path) {
+                    Some(suggestion) => {
+                        format!("unknown variable `{path}`, did you mean `{suggestion}`?")
+                    }
+                    None => format!("variable `{path}` is not declared in the schema"),
+                };
+                diagnostics.push(Diagnostic {
+                    file: file.clone(),
+                    location: locate(tmpl.source(), &path),
+                    severity: Severity::Error,
+                    message,
+                });
+            }
+            all_used.insert(path);
+        }
+        diagnostics[first..].sort_by_key(|diagnostic| diagnostic.location);
+    }
+
+    for (path, _) in schema.paths.iter() {
+        let is_used = |path: &str| {
+            all_used
+                .iter()
+                .any(|used| is_below(used, path) || is_below(path, used))
+        };
+        // Only report the outermost unused path to keep the output short.
+        let parent_used = path
+            .rsplit_once('.')
+            .is_none_or(|(parent, _)| is_used(parent));
+        if !is_used(path) && parent_used {
+            diagnostics.push(Diagnostic {
+                file: schema_name.to_string(),
+                location: None,
+                severity: Severity::Warning,
+                message: format!("variable `{path}` is declared but never used"),
+            });
+        }
+    }
+    Ok(diagnostics)
+}
+
+fn root(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+/// Finds the first place `path` is looked up in `source`.
+///
+/// Looks for the full `a.b.c` attribute chain first and falls back to the
+/// first occurrence of the root name.
+fn locate(source: &str, path: &str) -> Option<(usize, usize)> {
+    let tokens: Vec<_> = super::tokens(source).collect();
+    let parts: Vec<&str> = path.split('.').collect();
+
+    let matches_at = |start: usize| {
+        parts.iter().enumerate().all(|(i, part)| {
+            let ident =
+                matches!(tokens.get(start + i * 2), Some((Token::Ident(name), _)) if name == part);
+            let dot = i == 0 || matches!(tokens.get(start + i * 2 - 1), Some((Token::Dot, _)));
+            ident && dot
+        })
+    };
+    let position = (0..tokens.len()).find(|&i| matches_at(i)).or_else(|| {
+        tokens
+            .iter()
+            .position(|(token, _)| matches!(token, Token::Ident(name) if *name == parts[0]))
+    })?;
+    let span = tokens[position].1;
+    Some((
+        usize::from(span.start_line),
+        usize::from(span.start_col) + 1,
+    ))
+}
+
+fn collect_templates(dir: &Path, prefix: &str, names: &mut Vec<String>) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        // The path loader refuses to load hidden files, so skip them here too.
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let name = format!("{prefix}{file_name}");
+        if entry.file_type()?.is_dir() {
+            collect_templates(&entry.path(), &format!("{name}/"), names)?;
+        } else {
+            names.push(name);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["templates", "schema"], &["deny-unused"])?;
+    let dir = args.required("templates")?;
+    let schema_path = args.required("schema")?;
+    if !args.positional().is_empty() {
+        return Err(UsageError("lint takes no positional arguments".into()).into());
+    }
+
+    let json: Json = serde_json::from_str(
+        &fs::read_to_string(schema_path).map_err(|err| format!("{schema_path}: {err}"))?,
+    )
+    .map_err(|err| format!("{schema_path}: {err}"))?;
+    let schema = Schema::from_json(&json).map_err(|err| format!("{schema_path}: {err}"))?;
+
+    let diagnostics = lint_dir(Path::new(dir), &schema, schema_path)?;
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+    let failing = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error || args.flag("deny-unused"))
+        .count();
+    if failing > 0 {
+        return Err(format!("{failing} problem(s) found").into());
+    }
+    Ok(())
+}
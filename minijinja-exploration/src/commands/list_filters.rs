@@ -0,0 +1,35 @@
+//! `list-filters`: documents the custom filters and functions.
+
+use super::{Args, CommandResult, UsageError};
+use crate::registry::{self, Kind};
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration list-filters [--kind filter|function] [NAME...]
+
+Lists the custom filters and functions with a short description, or shows
+the full documentation of the given NAMEs.  Templates can read the same
+documentation with help(\"NAME\").";
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["kind"], &[])?;
+    let kinds = match args.value("kind") {
+        None => vec![Kind::Filter, Kind::Function],
+        Some("filter") => vec![Kind::Filter],
+        Some("function") => vec![Kind::Function],
+        Some(other) => return Err(UsageError(format!("unknown kind `{other}`")).into()),
+    };
+
+    if args.positional().is_empty() {
+        print!("{}", registry::listing(&kinds));
+        return Ok(());
+    }
+    let mut docs = Vec::new();
+    for name in args.positional() {
+        match registry::find(name) {
+            Some(entry) if kinds.contains(&entry.kind) => docs.push(entry.help()),
+            _ => return Err(UsageError(format!("no filter or function named `{name}`")).into()),
+        }
+    }
+    println!("{}", docs.join("\n\n"));
+    Ok(())
+}
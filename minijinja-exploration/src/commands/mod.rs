@@ -0,0 +1,132 @@
+//! Subcommands of the `minijinja-exploration` binary.
+//!
+//! Every command takes the arguments that follow its name and returns a
+//! [`CommandResult`].  Bad invocations are reported as a [`UsageError`] so
+//! the binary can tell them apart from failures while rendering.
+
+use minijinja::machinery::{Span, Token, WhitespaceConfig, ast, tokenize};
+use minijinja::syntax::SyntaxConfig;
+use std::error::Error;
+use std::fmt;
+
+pub mod dump;
+pub mod lint;
+pub mod list_filters;
+pub mod render;
+pub mod repl;
+pub mod svg;
+pub mod watch;
+
+/// The syntax and whitespace settings of a default [`minijinja::Environment`].
+// `SyntaxConfig` is only a unit struct while `custom_syntax` is disabled.
+#[allow(clippy::default_constructed_unit_structs)]
+fn default_config() -> (SyntaxConfig, WhitespaceConfig) {
+    (SyntaxConfig::default(), WhitespaceConfig::default())
+}
+
+/// The tokens of `source` up to the first one the lexer rejects.
+pub(crate) fn tokens(source: &str) -> impl Iterator<Item = (Token<'_>, Span)> {
+    let (syntax, whitespace) = default_config();
+    tokenize(source, false, syntax, whitespace).map_while(Result::ok)
+}
+
+/// Parses `source` the way the default environment would.
+pub(crate) fn parse<'source>(
+    name: &'source str,
+    source: &'source str,
+) -> Result<ast::Stmt<'source>, minijinja::Error> {
+    let (syntax, whitespace) = default_config();
+    minijinja::machinery::parse(source, name, syntax, whitespace)
+}
+
+/// Result returned by every subcommand.
+pub type CommandResult = Result<(), Box<dyn Error>>;
+
+/// The command line could not be understood.
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+/// Parsed command line of a single subcommand.
+///
+/// Options are written as `--name value` or `--name=value` and may repeat;
+/// flags are bare `--name` switches.  Everything else is positional.
+#[derive(Debug, Default)]
+pub struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Parses `args`, accepting only the given options and flags.
+    pub fn parse(args: &[String], options: &[&str], flags: &[&str]) -> Result<Args, UsageError> {
+        let mut rv = Args::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                rv.positional.push(arg.clone());
+                continue;
+            };
+            let (name, inline_value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+            if options.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| UsageError(format!("--{name} requires a value")))?,
+                };
+                rv.options.push((name.to_string(), value));
+            } else if flags.contains(&name) && inline_value.is_none() {
+                rv.flags.push(name.to_string());
+            } else {
+                return Err(UsageError(format!("unknown option `{arg}`")));
+            }
+        }
+        Ok(rv)
+    }
+
+    /// Positional arguments in the order they were given.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// The last value given for `name`, if any.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All values given for a repeatable option.
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.options
+            .iter()
+            .filter(move |(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Like [`value`](Self::value) but fails if the option is missing.
+    pub fn required(&self, name: &str) -> Result<&str, UsageError> {
+        self.value(name)
+            .ok_or_else(|| UsageError(format!("missing required option --{name}")))
+    }
+
+    /// Whether the flag `name` was passed.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+}
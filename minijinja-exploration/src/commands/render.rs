@@ -0,0 +1,122 @@
+//! `render`: renders a template from a directory with JSON context files.
+
+use super::{Args, CommandResult, UsageError};
+use crate::introspection;
+use crate::point::convert_points;
+use crate::theme::ThemeLoader;
+use minijinja::value::merge_maps;
+use minijinja::{Environment, Value};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration render --templates [LAYER=]DIR... [--context FILE]... [--output FILE] [--points] [--origins] NAME
+
+Renders the template NAME loaded from DIR.  With several --templates, the
+directories are searched in the order given, so put overrides first.  Each
+layer is named LAYER or after its directory and names must be unique;
+`LAYER:name` starts the search at that layer, e.g.
+`{% extends \"theme:base.html\" %}`.  --origins prints the file each
+template was loaded from to stderr.  Each --context file must contain
+a JSON object; when several are given, keys in later files override keys in
+earlier ones.  Use `-` to read a context from stdin.  Output goes to stdout
+unless --output is given.  With --points, maps with exactly the keys x, y
+and z and arrays of exactly three numbers in the context become points.";
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(
+        args,
+        &["templates", "context", "output"],
+        &["points", "origins"],
+    )?;
+    args.required("templates")?;
+    let [name] = args.positional() else {
+        return Err(UsageError("expected exactly one template name".into()).into());
+    };
+
+    let mut env = Environment::new();
+    crate::add_extensions(&mut env);
+    let loader = theme_loader(args.values("templates"))?;
+    let tracked = loader.clone();
+    introspection::track_includes(&mut env, move |name| tracked.load(name));
+    // Rendered files should end the way their templates do.
+    env.set_keep_trailing_newline(true);
+    let tmpl = env.get_template(name)?;
+    let mut ctx = load_context(args.values("context"))?;
+    if args.flag("points") {
+        ctx = convert_points(ctx);
+    }
+
+    match args.value("output") {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            tmpl.render_to_write(ctx, &mut out)?;
+            out.flush()?;
+        }
+        None => {
+            tmpl.render_to_write(ctx, io::stdout().lock())?;
+        }
+    }
+    if args.flag("origins") {
+        for (name, origin) in loader.origins() {
+            eprintln!("{name}: {origin}");
+        }
+    }
+    Ok(())
+}
+
+/// Builds a loader from `[LAYER=]DIR` arguments, highest precedence first.
+///
+/// Layers without an explicit name are named after their directory.  Two
+/// layers with the same name are a usage error.
+pub fn theme_loader<'a, I>(layers: I) -> Result<ThemeLoader, UsageError>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    layers
+        .into_iter()
+        .try_fold(ThemeLoader::new(), |loader, layer| {
+            let (name, dir) = match layer.split_once('=') {
+                Some((name, dir)) => (name.into(), dir),
+                None => {
+                    let name = Path::new(layer)
+                        .file_name()
+                        .map_or(layer.into(), |name| name.to_string_lossy());
+                    (name, layer)
+                }
+            };
+            loader.with_layer(name, dir).map_err(|err| {
+                let detail = err.detail().unwrap_or_default();
+                UsageError(format!("--templates {layer}: {detail}, use LAYER=DIR"))
+            })
+        })
+}
+
+/// Loads and merges JSON context files, later files taking precedence.
+pub fn load_context<'a, I>(paths: I) -> Result<Value, Box<dyn std::error::Error>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut maps = Vec::new();
+    for path in paths {
+        let value = read_json(path)?;
+        if !value.is_object() {
+            return Err(format!("{path}: context must be a JSON object").into());
+        }
+        maps.push(Value::from_serialize(value));
+    }
+    Ok(merge_maps(maps))
+}
+
+fn read_json(path: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let mut source = String::new();
+    if path == "-" {
+        io::stdin().read_to_string(&mut source)?;
+    } else {
+        File::open(Path::new(path))
+            .and_then(|mut file| file.read_to_string(&mut source))
+            .map_err(|err| format!("{path}: {err}"))?;
+    }
+    serde_json::from_str(&source).map_err(|err| format!("{path}: {err}").into())
+}
@@ -0,0 +1,189 @@
+//! `repl`: evaluates expressions interactively with `compile_expression`.
+
+use super::{Args, CommandResult, render};
+use minijinja::value::Value;
+use minijinja::{Environment, Error};
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration repl [--context FILE]... [--history FILE]
+
+Starts an interactive prompt that evaluates minijinja expressions.  The
+--context files are loaded as with `render`.  Input history is kept in
+--history (default: ~/.minijinja-exploration-history).";
+
+const HELP: &str = "\
+Type an expression to evaluate it, e.g. `name | slugify` or
+`mathematical_fold(1, 2, 3, op='mul')`.  Commands:
+  :set NAME = EXPR  evaluate EXPR and store it as NAME
+  :unset NAME       remove a variable
+  :vars             list the variables in the context
+  :load FILE        merge the variables of a JSON file into the context
+  :help             show this message
+  :quit             leave the REPL";
+
+/// What the REPL wants the caller to do after handling a line.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// Print the text and keep reading.
+    Print(String),
+    /// Nothing to show; keep reading.
+    Continue,
+    /// Stop reading input.
+    Quit,
+}
+
+/// The evaluation state behind the interactive prompt.
+///
+/// Expressions are compiled against an environment with all of the crate's
+/// filters and functions and evaluated with the current variables as context.
+pub struct Repl {
+    env: Environment<'static>,
+    vars: BTreeMap<String, Value>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Repl {
+        let mut env = Environment::new();
+        crate::add_extensions(&mut env);
+        Repl {
+            env,
+            vars: BTreeMap::new(),
+        }
+    }
+
+    /// The variables visible to expressions.
+    pub fn vars(&self) -> &BTreeMap<String, Value> {
+        &self.vars
+    }
+
+    /// Evaluates `expr` against the current variables.
+    pub fn eval(&self, expr: &str) -> Result<Value, Error> {
+        let expr = self.env.compile_expression_owned(expr.to_string())?;
+        expr.eval(Value::from_iter(
+            self.vars.iter().map(|(k, v)| (k.clone(), v.clone())),
+        ))
+    }
+
+    /// Merges the top-level keys of a JSON object file into the variables.
+    pub fn load_context(&mut self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = render::load_context([path])?;
+        for key in ctx.try_iter()? {
+            let value = ctx.get_item(&key)?;
+            self.vars.insert(key.to_string(), value);
+        }
+        Ok(())
+    }
+
+    /// Handles one line of input.
+    pub fn handle_line(&mut self, line: &str) -> Result<Outcome, Box<dyn std::error::Error>> {
+        let line = line.trim();
+        let Some(command) = line.strip_prefix(':') else {
+            if line.is_empty() {
+                return Ok(Outcome::Continue);
+            }
+            return Ok(Outcome::Print(describe(&self.eval(line)?)));
+        };
+
+        let (command, rest) = command
+            .split_once(char::is_whitespace)
+            .map_or((command, ""), |(command, rest)| (command, rest.trim()));
+        match command {
+            "set" => {
+                let Some((name, expr)) = rest.split_once('=') else {
+                    return Err("usage: :set NAME = EXPR".into());
+                };
+                let name = name.trim();
+                if !is_identifier(name) {
+                    return Err(format!("invalid variable name `{name}`").into());
+                }
+                let value = self.eval(expr.trim())?;
+                let rv = describe(&value);
+                self.vars.insert(name.to_string(), value);
+                Ok(Outcome::Print(format!("{name} = {rv}")))
+            }
+            "unset" => match self.vars.remove(rest) {
+                Some(_) => Ok(Outcome::Continue),
+                None => Err(format!("no variable named `{rest}`").into()),
+            },
+            "vars" => Ok(Outcome::Print(
+                self.vars
+                    .iter()
+                    .map(|(name, value)| format!("{name} = {}", describe(value)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            "load" => {
+                self.load_context(rest)?;
+                Ok(Outcome::Print(format!("loaded {rest}")))
+            }
+            "help" => Ok(Outcome::Print(HELP.into())),
+            "quit" | "q" | "exit" => Ok(Outcome::Quit),
+            _ => Err(format!("unknown command `:{command}`, try :help").into()),
+        }
+    }
+}
+
+/// Formats a value together with its kind, e.g. `"abc" (string)`.
+pub fn describe(value: &Value) -> String {
+    format!("{value:?} ({})", value.kind())
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+fn default_history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".minijinja-exploration-history"))
+}
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["context", "history"], &[])?;
+    let mut repl = Repl::new();
+    for path in args.values("context") {
+        repl.load_context(path)?;
+    }
+
+    let history = args
+        .value("history")
+        .map(PathBuf::from)
+        .or_else(default_history_path);
+    let mut editor = DefaultEditor::new()?;
+    if let Some(path) = &history {
+        // A missing history file just means this is the first session.
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        match editor.readline(">> ") {
+            Ok(line) => {
+                editor.add_history_entry(line.as_str())?;
+                match repl.handle_line(&line) {
+                    Ok(Outcome::Print(text)) => println!("{text}"),
+                    Ok(Outcome::Continue) => {}
+                    Ok(Outcome::Quit) => break,
+                    Err(err) => eprintln!("error: {err:#}"),
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    if let Some(path) = &history {
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
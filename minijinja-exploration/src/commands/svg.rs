@@ -0,0 +1,42 @@
+//! `svg`: renders points and shapes from a JSON file into an SVG document.
+
+use super::{Args, CommandResult, UsageError, render};
+use crate::point::convert_points;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration svg [--output FILE] [--template NAME] INPUT
+
+Renders the shapes in the JSON file INPUT with the bundled SVG templates.
+INPUT is an object with optional `title`, `padding`, `width` and `height`
+keys and lists of `points`, `polygons` and `paths`.  Points are written as
+[x, y, z] or {\"x\": .., \"y\": .., \"z\": .., \"label\": ..}; polygons and
+paths are lists of points or objects with `points` and optional `label`,
+`fill` and `stroke` keys.  --template defaults to svg/document.svg.";
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["output", "template"], &[])?;
+    let [input] = args.positional() else {
+        return Err(UsageError("expected exactly one input file".into()).into());
+    };
+
+    let env = crate::svg::environment();
+    let tmpl = env.get_template(args.value("template").unwrap_or("svg/document.svg"))?;
+    let ctx = convert_points(render::load_context([input.as_str()])?);
+
+    match args.value("output") {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            tmpl.render_to_write(ctx, &mut out)?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+        None => {
+            let mut out = io::stdout().lock();
+            tmpl.render_to_write(ctx, &mut out)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,317 @@
+//! `watch`: re-renders templates whenever files in the template directory
+//! change.
+//!
+//! Templates are loaded through a path loader.  When a file changes the
+//! environment's template cache is cleared so the next render picks up the
+//! new source, and every output whose template reaches the changed file
+//! through `extends`, `include`, `import` or `from` is rendered again.
+
+use super::{Args, CommandResult, UsageError, render};
+use minijinja::machinery::Token;
+use minijinja::{Environment, Value, path_loader};
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: minijinja-exploration watch --templates DIR --output-dir OUT [--context FILE]... NAME...
+
+Renders each template NAME from DIR to OUT/NAME, then watches DIR and the
+--context files and re-renders the affected outputs on every change.";
+
+/// How long to wait for more events after a change before re-rendering, so
+/// an editor saving several files results in a single rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The templates a template refers to by name.
+#[derive(Debug, Default, PartialEq)]
+pub struct Dependencies {
+    /// Names given as string literals.
+    pub names: BTreeSet<String>,
+    /// Set if a name is computed at render time and cannot be known.
+    pub dynamic: bool,
+}
+
+/// Scans `source` for `extends`, `include`, `import` and `from` tags.
+pub fn dependencies(source: &str) -> Dependencies {
+    let mut rv = Dependencies::default();
+    let mut tokens = super::tokens(source).map(|(token, _)| token);
+
+    while let Some(token) = tokens.next() {
+        if !matches!(token, Token::BlockStart) {
+            continue;
+        }
+        let Some(Token::Ident("extends" | "include" | "import" | "from")) = tokens.next() else {
+            continue;
+        };
+        match static_names(&mut tokens) {
+            Some(names) => rv.names.extend(names),
+            None => rv.dynamic = true,
+        }
+    }
+    rv
+}
+
+/// Reads the template name after `extends`, `include`, `import` or `from`.
+///
+/// Returns `None` unless the name is a string literal, or a list of them for
+/// `include`, that is directly followed by the end of the tag or the rest of
+/// the tag's syntax.  `"a.html" ~ suffix` names a template that can't be known
+/// until render time.
+fn static_names<'a>(tokens: &mut impl Iterator<Item = Token<'a>>) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    let in_list = match tokens.next()? {
+        Token::Str(name) => {
+            names.push(name.to_string());
+            false
+        }
+        Token::String(name) => {
+            names.push(name.to_string());
+            false
+        }
+        Token::BracketOpen => true,
+        _ => return None,
+    };
+    if in_list {
+        loop {
+            match tokens.next()? {
+                Token::Str(name) => names.push(name.to_string()),
+                Token::String(name) => names.push(name.to_string()),
+                Token::Comma => {}
+                Token::BracketClose => break,
+                _ => return None,
+            }
+        }
+    }
+    match tokens.next()? {
+        Token::BlockEnd | Token::Ident("import" | "ignore" | "with" | "without" | "as") => {
+            Some(names)
+        }
+        _ => None,
+    }
+}
+
+/// A set of templates rendered from one directory into another.
+pub struct Site {
+    env: Environment<'static>,
+    template_dir: PathBuf,
+    output_dir: PathBuf,
+    context_files: Vec<String>,
+    context: Value,
+    targets: Vec<String>,
+}
+
+impl Site {
+    pub fn new(
+        template_dir: &Path,
+        output_dir: &Path,
+        context_files: Vec<String>,
+        targets: Vec<String>,
+    ) -> Result<Site, Box<dyn std::error::Error>> {
+        let mut env = Environment::new();
+        crate::add_extensions(&mut env);
+        env.set_loader(path_loader(template_dir));
+        env.set_keep_trailing_newline(true);
+        let context = render::load_context(context_files.iter().map(String::as_str))?;
+        Ok(Site {
+            env,
+            template_dir: template_dir.to_path_buf(),
+            output_dir: output_dir.to_path_buf(),
+            context_files,
+            context,
+            targets,
+        })
+    }
+
+    /// The targets whose output depends on any of the `changed` templates.
+    pub fn affected(&self, changed: &BTreeSet<String>) -> Vec<String> {
+        self.targets
+            .iter()
+            .filter(|target| self.depends_on(target, changed))
+            .cloned()
+            .collect()
+    }
+
+    fn depends_on(&self, target: &str, changed: &BTreeSet<String>) -> bool {
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![target.to_string()];
+        while let Some(name) = pending.pop() {
+            if changed.contains(&name) {
+                return true;
+            }
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            // A template that can't be read any more was probably deleted;
+            // rendering it again will report that.
+            let Ok(source) = fs::read_to_string(self.template_dir.join(&name)) else {
+                return true;
+            };
+            let deps = dependencies(&source);
+            if deps.dynamic {
+                return true;
+            }
+            pending.extend(deps.names);
+        }
+        false
+    }
+
+    /// Renders `targets`, reporting each result on `log`.
+    ///
+    /// Returns the number of targets that failed to render.
+    pub fn render(&mut self, targets: &[String], log: &mut dyn Write) -> io::Result<usize> {
+        self.env.clear_templates();
+        let mut failed = 0;
+        for target in targets {
+            match self.render_one(target) {
+                Ok(path) => writeln!(log, "rendered {target} -> {}", path.display())?,
+                Err(err) => {
+                    failed += 1;
+                    writeln!(log, "error: {target}: {err:#}")?;
+                }
+            }
+        }
+        Ok(failed)
+    }
+
+    fn render_one(&self, target: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let tmpl = self.env.get_template(target)?;
+        let path = self.output_dir.join(target);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = BufWriter::new(fs::File::create(&path)?);
+        tmpl.render_to_write(&self.context, &mut out)?;
+        out.flush()?;
+        Ok(path)
+    }
+
+    /// Renders every target.
+    pub fn render_all(&mut self, log: &mut dyn Write) -> io::Result<usize> {
+        let targets = self.targets.clone();
+        self.render(&targets, log)
+    }
+
+    /// Reacts to changes of the given files and returns the number of
+    /// targets that failed to render.
+    pub fn rebuild(&mut self, changed: &[PathBuf], log: &mut dyn Write) -> io::Result<usize> {
+        let context_changed = changed.iter().any(|path| {
+            self.context_files
+                .iter()
+                .any(|file| is_same_file(path, Path::new(file)))
+        });
+        if context_changed {
+            match render::load_context(self.context_files.iter().map(String::as_str)) {
+                Ok(context) => self.context = context,
+                Err(err) => {
+                    writeln!(log, "error: {err}")?;
+                    return Ok(self.targets.len());
+                }
+            }
+            return self.render_all(log);
+        }
+
+        let root = self.template_dir.canonicalize()?;
+        let names: BTreeSet<String> = changed
+            .iter()
+            .filter_map(|path| template_name(&root, path))
+            .collect();
+        let affected = self.affected(&names);
+        self.render(&affected, log)
+    }
+
+    /// Watches the template directory and the context files with `watcher`.
+    ///
+    /// Context files are watched through their directories: editors that
+    /// save by renaming a new file over the old one would otherwise end the
+    /// watch after the first save.
+    pub fn watch(&self, watcher: &mut impl Watcher) -> notify::Result<()> {
+        watcher.watch(&self.template_dir, RecursiveMode::Recursive)?;
+        let dirs: BTreeSet<&Path> = self
+            .context_files
+            .iter()
+            .map(|file| parent_dir(Path::new(file)))
+            .collect();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(())
+    }
+}
+
+/// The directory containing `path`, which is `.` for a bare file name.
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Whether `a` and `b` name the same file.  Compares the directories
+/// rather than the files, which may be gone or replaced.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    let same_dir = match (parent_dir(a).canonicalize(), parent_dir(b).canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => parent_dir(a) == parent_dir(b),
+    };
+    same_dir && a.file_name() == b.file_name()
+}
+
+/// The loader name of `path` if it lies inside the template directory.
+fn template_name(root: &Path, path: &Path) -> Option<String> {
+    // Deleted files can't be canonicalized, but their parent usually can.
+    let path = match path.canonicalize() {
+        Ok(path) => path,
+        Err(_) => path.parent()?.canonicalize().ok()?.join(path.file_name()?),
+    };
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(parts.join("/"))
+}
+
+pub fn run(args: &[String]) -> CommandResult {
+    let args = Args::parse(args, &["templates", "output-dir", "context"], &[])?;
+    let template_dir = Path::new(args.required("templates")?);
+    let output_dir = Path::new(args.required("output-dir")?);
+    if args.positional().is_empty() {
+        return Err(UsageError("expected at least one template name".into()).into());
+    }
+    let mut site = Site::new(
+        template_dir,
+        output_dir,
+        args.values("context").map(str::to_string).collect(),
+        args.positional().to_vec(),
+    )?;
+    let mut log = io::stderr();
+    site.render_all(&mut log)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    site.watch(&mut watcher)?;
+    writeln!(log, "watching {} for changes", template_dir.display())?;
+
+    while let Ok(event) = rx.recv() {
+        let mut changed = Vec::new();
+        let mut event = Some(event);
+        // Collect everything that arrives in quick succession.
+        while let Some(result) = event {
+            match result {
+                Ok(event) if !event.kind.is_access() => changed.extend(event.paths),
+                Ok(_) => {}
+                Err(err) => writeln!(log, "watch error: {err}")?,
+            }
+            event = rx.recv_timeout(DEBOUNCE).ok();
+        }
+        if !changed.is_empty() {
+            site.rebuild(&changed, &mut log)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! The `templates/` directory, compiled into the binary by `build.rs`.
+//!
+//! [`EmbeddedLoader`] serves the bundled templates under the same names and
+//! with the same rules as `path_loader("templates")`, so a release binary
+//! needs no template files next to it.  During development,
+//! [`EmbeddedLoader::prefer_disk`] (or setting [`DISK_ENV`]) reads templates
+//! from a directory first so edits show up without a rebuild.
+//!
+//! With the `compress` feature the templates are stored deflated and
+//! inflated when they are loaded.
+
+use minijinja::{Environment, Error, ErrorKind, path_loader};
+use std::path::{Path, PathBuf};
+
+include!(concat!(env!("OUT_DIR"), "/embedded_templates.rs"));
+
+/// The environment variable [`EmbeddedLoader::from_env`] reads a template
+/// directory from.
+pub const DISK_ENV: &str = "MINIJINJA_EXPLORATION_TEMPLATES";
+
+/// The names of all embedded templates, sorted.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    TEMPLATES.iter().map(|(name, _)| *name)
+}
+
+/// The source of the embedded template `name`.
+pub fn get(name: &str) -> Result<Option<String>, Error> {
+    let Some(name) = normalize(name) else {
+        return Ok(None);
+    };
+    let Some((_, bytes)) = TEMPLATES.iter().find(|(n, _)| *n == name) else {
+        return Ok(None);
+    };
+    let bytes = inflate(bytes).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("could not decompress embedded template {name}: {err}"),
+        )
+    })?;
+    String::from_utf8(bytes).map(Some).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("embedded template {name} is not valid UTF-8"),
+        )
+        .with_source(err)
+    })
+}
+
+/// Turns a template name into the key it is embedded under, the way
+/// `path_loader` turns it into a path: hidden segments, `..` and
+/// backslashes are refused and empty segments are dropped.
+fn normalize(name: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in name.split('/') {
+        if segment.starts_with('.') || segment.contains('\\') {
+            return None;
+        }
+        if !segment.is_empty() {
+            segments.push(segment);
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[cfg(feature = "compress")]
+fn inflate(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    miniz_oxide::inflate::decompress_to_vec(bytes).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "compress"))]
+fn inflate(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    Ok(bytes.to_vec())
+}
+
+/// Loads the embedded templates, optionally preferring files on disk.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedLoader {
+    disk: Option<PathBuf>,
+}
+
+impl EmbeddedLoader {
+    /// A loader that only uses the embedded templates.
+    pub fn new() -> EmbeddedLoader {
+        EmbeddedLoader::default()
+    }
+
+    /// A loader that looks in `dir` first and falls back to the embedded
+    /// templates for files that are not there.
+    pub fn prefer_disk(dir: impl Into<PathBuf>) -> EmbeddedLoader {
+        EmbeddedLoader {
+            disk: Some(dir.into()),
+        }
+    }
+
+    /// Like [`prefer_disk`](Self::prefer_disk) with the directory in
+    /// [`DISK_ENV`], or [`new`](Self::new) if it is unset or empty.
+    pub fn from_env() -> EmbeddedLoader {
+        match std::env::var_os(DISK_ENV) {
+            Some(dir) if !dir.is_empty() => EmbeddedLoader::prefer_disk(dir),
+            _ => EmbeddedLoader::new(),
+        }
+    }
+
+    /// The directory searched before the embedded templates, if any.
+    pub fn disk_dir(&self) -> Option<&Path> {
+        self.disk.as_deref()
+    }
+
+    pub fn load(&self, name: &str) -> Result<Option<String>, Error> {
+        if let Some(dir) = &self.disk
+            && let Some(source) = path_loader(dir)(name)?
+        {
+            return Ok(Some(source));
+        }
+        get(name)
+    }
+
+    /// Makes this the loader of `env`.
+    pub fn install(&self, env: &mut Environment<'_>) {
+        let loader = self.clone();
+        env.set_loader(move |name| loader.load(name));
+    }
+}
@@ -0,0 +1,608 @@
+use crate::embedded::{self, EmbeddedLoader};
+use crate::filters::unique_slugs;
+use crate::limits::Limits;
+use crate::point::{Point, convert_points};
+use crate::registry;
+use minijinja::Environment;
+use minijinja::context;
+use minijinja::value::{Kwargs, Value};
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Result returned by every example; assertion failures panic instead.
+pub type ExampleResult = Result<(), Box<dyn std::error::Error>>;
+
+fn test_template_usage(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template("hello", "Hello {{ name }}!")?;
+    let tmpl = env.get_template("hello")?;
+    writeln!(out, "{}", tmpl.render(context!(name => "John"))?)?;
+    Ok(())
+}
+
+fn test_expression_usage(out: &mut dyn Write) -> ExampleResult {
+    let env = Environment::new();
+    let expr = env.compile_expression("number < 42")?;
+    let result = expr.eval(context! (number => 23))?;
+    assert!(result.is_true());
+    writeln!(out, "number < 42 with number=23: {result}")?;
+    Ok(())
+}
+
+// Dynamic objects
+fn test_dynamic_objects(out: &mut dyn Write) -> ExampleResult {
+    let value = Value::from_object(Point(1.0, 2.5, 3.0));
+    if let Some(object) = value.as_object() {
+        assert_eq!(object.get_value(&Value::from("x")), Some(Value::from(1.0)));
+        assert_eq!(object.get_value(&Value::from("y")), Some(Value::from(2.5)));
+        assert_eq!(object.get_value(&Value::from("z")), Some(Value::from(3.0)));
+    }
+    writeln!(out, "{value:?}")?;
+    Ok(())
+}
+
+fn test_point_methods(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template(
+        "geometry",
+        "|p| = {{ p.length() }}\n\
+         p . q = {{ p.dot(q) }}\n\
+         p x q = {{ p.cross(q).x }}, {{ p.cross(q).y }}, {{ p.cross(q).z }}\n\
+         unit p = {{ p.normalize().x | round(4) }}, {{ p.normalize().y | round(4) }}\n\
+         2p = {{ p.scale(2).x }}, {{ p.scale(2).y }}\n\
+         d(p, q) = {{ p.distance(q) | round(4) }}",
+    )?;
+    let tmpl = env.get_template("geometry")?;
+    let ctx = context! {
+        p => Value::from_object(Point(3.0, 4.0, 0.0)),
+        q => Value::from_object(Point(0.0, 0.0, 1.0)),
+    };
+    writeln!(out, "{}", tmpl.render(ctx)?)?;
+    Ok(())
+}
+
+fn test_point_rendering(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["format_point"])?;
+    env.add_template(
+        "point",
+        "default: {{ p }}\n\
+         fixed: {{ p | format_point(precision=2) }}\n\
+         json style: {{ p | format_point(precision=1, style='json') }}\n\
+         tojson: {{ p | tojson }}",
+    )?;
+    let tmpl = env.get_template("point")?;
+    let ctx = context!(p => Value::from_object(Point(1.0, 2.5, 3.0)));
+    writeln!(out, "{}", tmpl.render(ctx)?)?;
+    Ok(())
+}
+
+fn test_points_from_context(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["point"])?;
+    env.add_template(
+        "points",
+        "{% for p in path %}{{ p }} |p|={{ p.length() | round(2) }}\n{% endfor %}\
+         {{ point(1, 0, 0).cross(point(0, 1, 0)) }}",
+    )?;
+    let tmpl = env.get_template("points")?;
+    // As if loaded from a JSON file: one map and one array.
+    let ctx = Value::from_serialize(serde_json::json!({
+        "path": [{"x": 3, "y": 4, "z": 0}, [1, 2, 2]],
+    }));
+    writeln!(out, "{}", tmpl.render(convert_points(ctx))?)?;
+    Ok(())
+}
+
+fn test_geometry_objects(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["polygon", "path"])?;
+    env.add_template(
+        "report",
+        "{% set poly = polygon(square) %}\
+         {% for p in poly %}corner {{ loop.index }}: {{ p }}\n{% endfor %}\
+         corners: {{ poly | length }}\n\
+         area: {{ poly.area() }}, perimeter: {{ poly.perimeter() }}\n\
+         centroid: {{ poly.centroid() }}\n\
+         {% set box = poly.bbox() %}\
+         bbox: {{ box.min }} .. {{ box.max }}, area {{ box.area() }}\n\
+         contains centroid: {{ box.contains(poly.centroid()) }}\n\
+         route length: {{ path(square).length() }}",
+    )?;
+    let tmpl = env.get_template("report")?;
+    let square = vec![
+        Value::from_object(Point(0.0, 0.0, 0.0)),
+        Value::from_object(Point(4.0, 0.0, 0.0)),
+        Value::from_object(Point(4.0, 3.0, 0.0)),
+        Value::from_object(Point(0.0, 3.0, 0.0)),
+    ];
+    writeln!(out, "{}", tmpl.render(context!(square))?)?;
+    Ok(())
+}
+
+fn test_point_sorting(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["point"])?;
+    env.add_template(
+        "sorting",
+        "by z: {{ points | sort(attribute='z') | join(' ') }}\n\
+         sorted: {{ points | sort | join(' ') }}\n\
+         reversed: {{ points | sort(reverse=true) | first }}\n\
+         unique: {{ points | unique | join(' ') }}\n\
+         {% for group in points | groupby('z') %}\
+         z={{ group.grouper }}: {{ group.list | join(' ') }}\n\
+         {% endfor %}\
+         equal: {{ points[0] == point(1, 2, 3) }}, {{ points[0] == points[1] }}\n\
+         {% set names = {points[0]: 'a', points[1]: 'b'} %}\
+         lookup: {{ names[point(1, 2, 3)] }}",
+    )?;
+    let tmpl = env.get_template("sorting")?;
+    let points: Vec<Value> = [
+        Point(1.0, 2.0, 3.0),
+        Point(0.0, 5.0, 1.0),
+        Point(1.0, 2.0, 3.0),
+        Point(2.0, 0.0, 1.0),
+    ]
+    .into_iter()
+    .map(Value::from_object)
+    .collect();
+    writeln!(out, "{}", tmpl.render(context!(points))?)?;
+    Ok(())
+}
+
+// Custom filters
+fn test_custom_filters(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["repeat"])?;
+    env.add_template("hello", "{{ 'Na ' | repeat(3) }} {{ name }}!")?;
+    let tmpl = env.get_template("hello")?;
+    writeln!(out, "{}", tmpl.render(context! (name => "Batman"))?)?;
+    Ok(())
+}
+
+// Environment related
+// How to iterate through templates in an environment
+fn test_templates_iteration(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template("hello.txt", "Hello {{ name }}!")?;
+    env.add_template("goodbye.txt", "Goodbye {{ name }}!")?;
+
+    for (name, tmpl) in env.templates() {
+        writeln!(
+            out,
+            "The template {} renders to {}",
+            name,
+            tmpl.render(context! {name => "World"})?
+        )?;
+    }
+    Ok(())
+}
+
+// Getting a template by name
+fn test_get_template_by_name(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template("hello.txt", "Hello {{ name }} !")?;
+    let tmpl = env.get_template("hello.txt")?;
+    writeln!(out, "{}", tmpl.render(context! { name => "World" })?)?;
+    Ok(())
+}
+
+// Loading a template from a string
+fn test_loading_template_from_a_string(out: &mut dyn Write) -> ExampleResult {
+    let env = Environment::new();
+    let tmpl = env.template_from_named_str("template_name", "Hello {{ name }}")?;
+    let rv = tmpl.render(context! {name => "World"});
+    writeln!(out, "{}", rv?)?;
+    Ok(())
+}
+
+// Parsing and rendering a template from a string in one go
+fn test_parse_and_render_from_string_in_one_go(out: &mut dyn Write) -> ExampleResult {
+    let env = Environment::new();
+    let rv = env.render_named_str(
+        "template_name",
+        "Hello {{ name }}",
+        context! { name => "World" },
+    );
+    writeln!(out, "{}", rv?)?;
+    Ok(())
+}
+
+// Rendering and returning the evaluated state
+fn test_render_and_return_evaluated_state(out: &mut dyn Write) -> ExampleResult {
+    let env = Environment::new();
+    let tmpl = env.template_from_str("{% set x = 42 %}Hello {{ what }}!")?;
+    let (rv, state) = tmpl.render_and_return_state(context! { what => "World"})?;
+    assert_eq!(rv, "Hello World!");
+    assert_eq!(state.lookup("x"), Some(Value::from(42)));
+
+    // Render and send output straight to the writer
+    tmpl.render_to_write(context! { what => "John"}, &mut *out)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// Discard output and return internal state
+fn test_discard_output_and_return_internal_state(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template("hello", "Hello {{ name }}!")?;
+    let tmpl = env.get_template("hello")?;
+    let state = tmpl.eval_to_state(context! { name => "John"})?;
+    writeln!(out, "{:?}", state.exports())?;
+    Ok(())
+}
+
+// Returning undeclared variables in the template
+fn test_return_undeclared_variables(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    env.add_template("x", "{% set x = foo %}{{ x }}{{ bar.baz }}")?;
+    let tmpl = env.get_template("x")?;
+    let undeclared = tmpl.undeclared_variables(false);
+    assert_eq!(
+        undeclared,
+        HashSet::from(["foo".to_string(), "bar".to_string()])
+    );
+    let undeclared = tmpl.undeclared_variables(true);
+    assert_eq!(
+        undeclared,
+        HashSet::from(["foo".to_string(), "bar.baz".to_string()])
+    );
+    let mut sorted: Vec<_> = undeclared.into_iter().collect();
+    sorted.sort();
+    writeln!(out, "{sorted:?}")?;
+    Ok(())
+}
+
+// Custom filters
+fn test_custom_filters_example1_slugify(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["slugify"])?;
+
+    env.add_template("hello", "Hello {{ name | slugify }}!")?;
+    let tmpl = env.get_template("hello")?;
+    writeln!(out, "{}", tmpl.render(context!(name => "John Wild Oak"))?)?;
+
+    env.add_template(
+        "options",
+        "{{ title | slugify }}\n\
+         {{ title | slugify(separator='_', lowercase=false) }}\n\
+         {{ title | slugify(max_length=12) }}",
+    )?;
+    let tmpl = env.get_template("options")?;
+    writeln!(
+        out,
+        "{}",
+        tmpl.render(context!(title => "Crème Brûlée -- Grandma's Recipe!"))?
+    )?;
+
+    registry::register(&mut env, &["append_template"])?;
+    env.add_template("state_of_the_template", "{{ name | append_template }}")?;
+    let tmpl = env.get_template("state_of_the_template")?;
+    writeln!(out, "{}", tmpl.render(context!(name => "John Wild Oak"))?)?;
+    Ok(())
+}
+
+// Unique slugs for anchors, collected across a render
+fn test_unique_slugs(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["unique_slug"])?;
+    env.add_template(
+        "heading",
+        "<h2 id=\"{{ title | unique_slug }}\">{{ title }}</h2>",
+    )?;
+    env.add_template(
+        "doc",
+        "{% for title in titles %}{% include 'heading' %}\n{% endfor %}",
+    )?;
+    let tmpl = env.get_template("doc")?;
+    let titles = ["Usage", "Examples", "Usage", "Usage 2", "Usage!"];
+    let (rv, state) = tmpl.render_and_return_state(context!(titles))?;
+    write!(out, "{rv}")?;
+    for (slug, title) in unique_slugs(&state) {
+        writeln!(out, "{slug} <- {title:?}")?;
+    }
+    Ok(())
+}
+
+// Introspecting the render state
+fn test_context_info(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["context_info", "debug"])?;
+    env.add_template("base.html", "{% block body %}{% endblock %}")?;
+    env.add_template(
+        "page.html",
+        "{% extends 'base.html' %}\
+         {% block body %}{% set greeting = 'hi' %}{{ debug() }}{% endblock %}",
+    )?;
+    env.add_template(
+        "note.txt",
+        "{% macro shout(s) %}{{ s | upper }}{% endmacro %}\
+         {% set info = context_info() %}\
+         {{ info.template }} ({{ info.auto_escape }}): \
+         {% for name, kind in info.variables | items %}{{ name }}={{ kind }} {% endfor %}\n\
+         {{ debug() }}",
+    )?;
+    let ctx = context! {
+        user => context! { name => "<John>" },
+        origin => Value::from_object(Point(0.0, 0.0, 0.0)),
+        tags => vec!["a", "b"],
+    };
+    writeln!(out, "{}", env.get_template("page.html")?.render(&ctx)?)?;
+    writeln!(out, "{}", env.get_template("note.txt")?.render(&ctx)?)?;
+    Ok(())
+}
+
+// Fuel and output limits
+fn test_limits(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    let limits = Limits {
+        fuel: Some(1000),
+        max_output: 64,
+    };
+    limits.apply(&mut env);
+    env.add_template("ok", "{{ 'ab' | repeat(3) }}")?;
+    env.add_template("repeat", "{{ 'ab' | repeat(1000000000) }}")?;
+    env.add_template("loop", "{% for i in range(10000) %}{% endfor %}")?;
+    env.add_template("output", "{% for i in range(20) %}{{ i }}, {% endfor %}")?;
+    for name in ["ok", "repeat", "loop", "output"] {
+        match limits.render(&env.get_template(name)?, context!()) {
+            Ok(rv) => writeln!(out, "{name}: {rv}")?,
+            Err(err) => writeln!(out, "{name}: {err}")?,
+        }
+    }
+    Ok(())
+}
+
+// Keyword arguments
+fn test_kwarg_handling(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["modify"])?;
+    env.add_template("mod_vec", "{{ modify(input_vec, limit=4, reverse=true) }}")?;
+    let tmpl = env.get_template("mod_vec")?;
+    let my_input_vec = vec![1, 2, 4, 5, 6, 7, 8, 9];
+    writeln!(
+        out,
+        "{}",
+        tmpl.render(context! (input_vec => my_input_vec.clone()))?
+    )?;
+    env.add_template(
+        "mod_vec_toolkit",
+        "{{ modify(input_vec, filter='item is odd', offset=1, chunk=2) }}\n\
+         {{ modify(input_vec, shuffle=7, step=2) }}",
+    )?;
+    let tmpl = env.get_template("mod_vec_toolkit")?;
+    writeln!(
+        out,
+        "{}",
+        tmpl.render(context! (input_vec => my_input_vec))?
+    )?;
+
+    // How to create a Kwarg object from scratch
+    let kwargs_from_scratch =
+        Kwargs::from_iter([("foo", Value::from(true)), ("bar", Value::from(42))]);
+    let value_from_kwargs = Value::from(kwargs_from_scratch);
+    assert!(value_from_kwargs.is_kwargs());
+
+    // How to use Rest to handle variadic parameters
+    registry::register(&mut env, &["mathematical_fold"])?;
+    env.add_template("fold_mul", "{{ mathematical_fold(1,2,3,4, op = mul) }}")?;
+    let tmpl_mul = env.get_template("fold_mul")?;
+    writeln!(out, "{}", tmpl_mul.render(context! (mul => "mul"))?)?;
+    env.add_template("fold_add", "{{ mathematical_fold(1,2,3,4, op = add) }}")?;
+    let tmpl_add = env.get_template("fold_add")?;
+    writeln!(out, "{}", tmpl_add.render(context! (add => "add"))?)?;
+    Ok(())
+}
+
+// Reducing with a macro or any callable
+fn test_reduce(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    registry::register(&mut env, &["reduce"])?;
+    env.add_template(
+        "orders",
+        "{% macro add_price(total, order) %}{{ total + order.price * order.qty }}{% endmacro %}\
+         {% macro longer(a, b) %}{{ a if a | length >= b | length else b }}{% endmacro %}\
+         total: {{ reduce(orders, add_price, initial=0) }}\n\
+         doubled: {{ reduce(orders, add_price, initial=0) * 2 }}\n\
+         longest name: {{ reduce(orders | map(attribute='name'), longer) }}\n\
+         all tags: {{ reduce(orders | map(attribute='tags'), joiner, initial=[]) }}",
+    )?;
+    let tmpl = env.get_template("orders")?;
+    let ctx = context! {
+        orders => vec![
+            context! { name => "tea", price => 3, qty => 2, tags => vec!["hot"] },
+            context! { name => "cake", price => 4.5, qty => 1, tags => vec!["sweet"] },
+            context! { name => "lemonade", price => 2, qty => 3, tags => vec!["cold", "sweet"] },
+        ],
+        // A Rust closure works just as well as a macro.
+        joiner => Value::from_function(|acc: Vec<Value>, tags: Vec<Value>| {
+            acc.into_iter().chain(tags).collect::<Vec<_>>()
+        }),
+    };
+    writeln!(out, "{}", tmpl.render(ctx)?)?;
+    Ok(())
+}
+
+// Templates embedded at build time
+fn test_embedded_templates(out: &mut dyn Write) -> ExampleResult {
+    let mut env = Environment::new();
+    EmbeddedLoader::new().install(&mut env);
+    let name = "examples/greeting.txt";
+    let tmpl = env.get_template(name)?;
+    writeln!(
+        out,
+        "{}",
+        tmpl.render(context!(name => "World", self_name => name))?
+    )?;
+    writeln!(
+        out,
+        "bundled: {}",
+        embedded::names().collect::<Vec<_>>().join(", ")
+    )?;
+    Ok(())
+}
+
+/// A runnable example together with a one-line description for `list`.
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: fn(&mut dyn Write) -> ExampleResult,
+}
+
+/// All registered examples, in the order they appear in the documentation.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "test_template_usage",
+        description: "Add a template to an environment and render it",
+        run: test_template_usage,
+    },
+    Example {
+        name: "test_expression_usage",
+        description: "Compile and evaluate an expression against a context",
+        run: test_expression_usage,
+    },
+    Example {
+        name: "test_dynamic_objects",
+        description: "Read attributes from a dynamic Point object",
+        run: test_dynamic_objects,
+    },
+    Example {
+        name: "test_point_methods",
+        description: "Call vector methods on Point objects from a template",
+        run: test_point_methods,
+    },
+    Example {
+        name: "test_point_rendering",
+        description: "Render Point objects as text and JSON",
+        run: test_point_rendering,
+    },
+    Example {
+        name: "test_points_from_context",
+        description: "Turn JSON maps and arrays into Points",
+        run: test_points_from_context,
+    },
+    Example {
+        name: "test_geometry_objects",
+        description: "Iterate and summarize Polygon, Path and BoundingBox objects",
+        run: test_geometry_objects,
+    },
+    Example {
+        name: "test_point_sorting",
+        description: "Sort, deduplicate, group and compare Points",
+        run: test_point_sorting,
+    },
+    Example {
+        name: "test_custom_filters",
+        description: "Register str::repeat as a filter",
+        run: test_custom_filters,
+    },
+    Example {
+        name: "test_templates_iteration",
+        description: "Iterate over all templates in an environment",
+        run: test_templates_iteration,
+    },
+    Example {
+        name: "test_get_template_by_name",
+        description: "Look up a template by name",
+        run: test_get_template_by_name,
+    },
+    Example {
+        name: "test_loading_template_from_a_string",
+        description: "Load a named template from a string",
+        run: test_loading_template_from_a_string,
+    },
+    Example {
+        name: "test_parse_and_render_from_string_in_one_go",
+        description: "Parse and render a string template in one call",
+        run: test_parse_and_render_from_string_in_one_go,
+    },
+    Example {
+        name: "test_render_and_return_evaluated_state",
+        description: "Render and inspect the resulting state",
+        run: test_render_and_return_evaluated_state,
+    },
+    Example {
+        name: "test_discard_output_and_return_internal_state",
+        description: "Evaluate a template to its state without output",
+        run: test_discard_output_and_return_internal_state,
+    },
+    Example {
+        name: "test_return_undeclared_variables",
+        description: "List variables a template uses but does not declare",
+        run: test_return_undeclared_variables,
+    },
+    Example {
+        name: "test_custom_filters_example1_slugify",
+        description: "Custom slugify filter and a filter that reads State",
+        run: test_custom_filters_example1_slugify,
+    },
+    Example {
+        name: "test_unique_slugs",
+        description: "Deduplicate slugs within a render and list them afterwards",
+        run: test_unique_slugs,
+    },
+    Example {
+        name: "test_context_info",
+        description: "Dump template name, block, auto-escape mode and variables",
+        run: test_context_info,
+    },
+    Example {
+        name: "test_limits",
+        description: "Stop runaway templates with fuel and an output budget",
+        run: test_limits,
+    },
+    Example {
+        name: "test_kwarg_handling",
+        description: "Keyword arguments with Kwargs and variadics with Rest",
+        run: test_kwarg_handling,
+    },
+    Example {
+        name: "test_reduce",
+        description: "Fold a sequence with a template macro or a Rust callable",
+        run: test_reduce,
+    },
+    Example {
+        name: "test_embedded_templates",
+        description: "Load templates that build.rs compiled into the binary",
+        run: test_embedded_templates,
+    },
+];
+
+/// Returns the examples whose name matches `pattern`.
+///
+/// The pattern supports `*` (any run of characters) and `?` (any single
+/// character); without wildcards it must match the name exactly.
+pub fn find(pattern: &str) -> Vec<&'static Example> {
+    EXAMPLES
+        .iter()
+        .filter(|example| glob_match(pattern, example.name))
+        .collect()
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = name.as_bytes();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -0,0 +1,198 @@
+//! Custom filters used by the examples.
+
+use crate::kwargs::from_kwargs;
+use minijinja::value::{Kwargs, Object, Value};
+use minijinja::{Error, ErrorKind, State};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Options for [`slug`].
+#[derive(Debug, Clone)]
+pub struct SlugOptions<'a> {
+    /// Put between words, `-` by default.
+    pub separator: &'a str,
+    /// The longest slug to produce.  Slugs are cut after the last word that
+    /// fits; a single word that is too long is truncated.
+    pub max_length: Option<usize>,
+    /// Whether to lowercase the slug, `true` by default.
+    pub lowercase: bool,
+}
+
+impl Default for SlugOptions<'_> {
+    fn default() -> Self {
+        SlugOptions {
+            separator: "-",
+            max_length: None,
+            lowercase: true,
+        }
+    }
+}
+
+/// Turns `value` into an ASCII slug, e.g. `"Crème Brûlée!"` into
+/// `creme-brulee`.
+///
+/// Non-ASCII characters are transliterated, apostrophes are dropped and
+/// every other run of characters that are not ASCII letters or digits
+/// becomes a single separator.  As long as the separator contains no
+/// letters or digits, slugifying a slug returns it unchanged.
+pub fn slug(value: &str, options: &SlugOptions<'_>) -> String {
+    let ascii = deunicode::deunicode(value);
+    let words = ascii
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '\''))
+        .map(|word| word.replace('\'', ""))
+        .filter(|word| !word.is_empty());
+
+    let mut rv = String::new();
+    for mut word in words {
+        if options.lowercase {
+            word.make_ascii_lowercase();
+        }
+        let separator = if rv.is_empty() { "" } else { options.separator };
+        if let Some(max_length) = options.max_length
+            && rv.len() + separator.len() + word.len() > max_length
+        {
+            if rv.is_empty() {
+                // Only ASCII is left, so any byte index is a char boundary.
+                rv.push_str(&word[..max_length]);
+            }
+            break;
+        }
+        rv.push_str(separator);
+        rv.push_str(&word);
+    }
+    rv
+}
+
+/// The keyword arguments of `slugify` and `unique_slug`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SlugArgs {
+    separator: Option<String>,
+    max_length: Option<usize>,
+    lowercase: Option<bool>,
+}
+
+impl SlugArgs {
+    fn options(&self) -> SlugOptions<'_> {
+        let defaults = SlugOptions::default();
+        SlugOptions {
+            separator: self.separator.as_deref().unwrap_or(defaults.separator),
+            max_length: self.max_length,
+            lowercase: self.lowercase.unwrap_or(defaults.lowercase),
+        }
+    }
+}
+
+/// The `slugify` filter, see [`slug`].
+///
+/// Accepts the keyword arguments `separator`, `max_length` and `lowercase`,
+/// e.g. `{{ title | slugify(separator="_", max_length=20) }}`.
+pub fn slugify(value: &str, kwargs: Kwargs) -> Result<String, Error> {
+    let args: SlugArgs = from_kwargs("slugify", &kwargs)?;
+    Ok(slug(value, &args.options()))
+}
+
+/// The slug `unique_slug` uses for text without any letters or digits.
+pub const EMPTY_SLUG: &str = "section";
+
+/// The longest prefix of `value` that is at most `max_len` bytes long.
+fn truncate(value: &str, max_len: usize) -> &str {
+    let mut end = max_len.min(value.len());
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+/// The name of the temp value holding the [`SlugRegistry`] of a render.
+const SLUG_REGISTRY: &str = "unique_slug";
+
+/// The slugs handed out by `unique_slug` during one render.
+#[derive(Debug, Default)]
+pub struct SlugRegistry {
+    inner: Mutex<SlugRegistryInner>,
+}
+
+#[derive(Debug, Default)]
+struct SlugRegistryInner {
+    taken: HashSet<String>,
+    slugs: Vec<(String, String)>,
+}
+
+impl SlugRegistry {
+    /// Reserves `base`, or `base-2`, `base-3`, ... if it is already taken,
+    /// and remembers that it was made from `text`.
+    ///
+    /// An empty `base` becomes [`EMPTY_SLUG`].  To stay within
+    /// `max_length`, `base` is shortened to make room for the suffix.  Fails
+    /// once not even one character of `base` fits next to the suffix.
+    fn claim(&self, base: String, text: &str, options: &SlugOptions<'_>) -> Result<String, Error> {
+        let max_length = options.max_length.unwrap_or(usize::MAX);
+        let base = if base.is_empty() {
+            truncate(EMPTY_SLUG, max_length).to_string()
+        } else {
+            base
+        };
+        let mut inner = self.inner.lock().unwrap();
+        let mut slug = base.clone();
+        let mut n = 1;
+        while slug.is_empty() || inner.taken.contains(&slug) {
+            n += 1;
+            let suffix = format!("{}{n}", options.separator);
+            let head = truncate(&base, max_length.saturating_sub(suffix.len()))
+                .trim_end_matches(options.separator);
+            if head.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!(
+                        "max_length={max_length} leaves no room for a unique slug for {text:?}"
+                    ),
+                ));
+            }
+            slug = format!("{head}{suffix}");
+        }
+        inner.taken.insert(slug.clone());
+        inner.slugs.push((slug.clone(), text.to_string()));
+        Ok(slug)
+    }
+
+    /// The `(slug, text)` pairs in the order the slugs were handed out.
+    pub fn slugs(&self) -> Vec<(String, String)> {
+        self.inner.lock().unwrap().slugs.clone()
+    }
+}
+
+impl Object for SlugRegistry {}
+
+/// The `unique_slug` filter: like `slugify`, but every slug is only handed
+/// out once per render.
+///
+/// Later duplicates get `-2`, `-3`, ... appended, so headings with the same
+/// text still get distinct anchors.  The suffix counts towards `max_length`;
+/// it is an error if `max_length` can't hold at least one character of the
+/// slug and the suffix.  Text that makes an empty slug is given
+/// [`EMPTY_SLUG`].  Includes share the registry with the template including
+/// them.  Use [`unique_slugs`] to get all slugs after
+/// `render_and_return_state`.
+pub fn unique_slug(state: &State, value: &str, kwargs: Kwargs) -> Result<String, Error> {
+    let args: SlugArgs = from_kwargs("unique_slug", &kwargs)?;
+    let options = args.options();
+    let registry = state.get_or_set_temp_object(SLUG_REGISTRY, SlugRegistry::default);
+    registry.claim(slug(value, &options), value, &options)
+}
+
+/// The `(slug, text)` pairs `unique_slug` handed out while rendering with
+/// `state`, in order.
+pub fn unique_slugs(state: &State) -> Vec<(String, String)> {
+    state
+        .get_temp(SLUG_REGISTRY)
+        .and_then(|value| value.downcast_object::<SlugRegistry>())
+        .map(|registry| registry.slugs())
+        .unwrap_or_default()
+}
+
+/// Appends the name of the template being rendered to `value`.
+pub fn append_template(state: &State, value: &Value) -> String {
+    format!("{}-{}", value, state.name())
+}
@@ -0,0 +1,359 @@
+//! Custom global functions used by the examples.
+
+use crate::kwargs::from_kwargs;
+use minijinja::value::{Kwargs, Rest, Value, ValueKind, merge_maps};
+use minijinja::{Error, ErrorKind, State, context};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+fn invalid_kwarg(name: &str, detail: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidOperation,
+        format!("modify: invalid value for `{name}`: {detail}"),
+    )
+}
+
+/// Looks up a dotted attribute path such as `"user.name"` or `"tags.0"`.
+fn lookup_path(value: &Value, path: &str) -> Value {
+    path.split('.')
+        .try_fold(value.clone(), |value, part| match part.parse::<usize>() {
+            Ok(idx) => value.get_item_by_index(idx),
+            Err(_) => value.get_attr(part),
+        })
+        .unwrap_or_default()
+}
+
+/// Shuffles `values` with a Fisher-Yates shuffle driven by SplitMix64, so
+/// the same seed always gives the same order.
+fn shuffle(values: &mut [Value], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    for i in (1..values.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        values.swap(i, j);
+    }
+}
+
+/// The keyword arguments of [`modify`].
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ModifyOptions {
+    filter: Option<String>,
+    unique_by: Option<String>,
+    sort_by: Option<String>,
+    reverse: bool,
+    shuffle: Option<u64>,
+    offset: usize,
+    step: usize,
+    limit: Option<usize>,
+    chunk: Option<usize>,
+}
+
+impl Default for ModifyOptions {
+    fn default() -> Self {
+        ModifyOptions {
+            filter: None,
+            unique_by: None,
+            sort_by: None,
+            reverse: false,
+            shuffle: None,
+            offset: 0,
+            step: 1,
+            limit: None,
+            chunk: None,
+        }
+    }
+}
+
+/// Transforms a list according to its keyword arguments, applied in this
+/// order:
+///
+/// - `filter`: an expression kept items must satisfy.  It sees the item as
+///   `item` and, for maps, each of its keys, e.g. `filter="price > 3"`.
+/// - `unique_by`: keeps the first item for each value of an attribute path.
+/// - `sort_by`: sorts by an attribute path such as `"user.name"`.
+/// - `reverse`: reverses the list.
+/// - `shuffle`: shuffles with the given integer seed.
+/// - `offset`, `step` and `limit`: skip items, take every `step`th item and
+///   keep at most `limit` of them.
+/// - `chunk`: splits the result into lists of `chunk` items.
+pub fn modify(state: &State, mut values: Vec<Value>, options: Kwargs) -> Result<Value, Error> {
+    let ModifyOptions {
+        filter,
+        unique_by,
+        sort_by,
+        reverse,
+        shuffle: seed,
+        offset,
+        step,
+        limit,
+        chunk,
+    } = from_kwargs("modify", &options)?;
+    for (name, value) in [("step", Some(step)), ("chunk", chunk)] {
+        if value == Some(0) {
+            return Err(invalid_kwarg(name, "must be at least 1"));
+        }
+    }
+
+    if let Some(source) = filter {
+        let expr = state
+            .env()
+            .compile_expression(&source)
+            .map_err(|err| invalid_kwarg("filter", &err.to_string()))?;
+        let mut kept = Vec::with_capacity(values.len());
+        for item in values {
+            let ctx = match item.kind() {
+                ValueKind::Map => merge_maps([item.clone(), context! { item }]),
+                _ => context! { item },
+            };
+            let keep = expr
+                .eval(ctx)
+                .map_err(|err| invalid_kwarg("filter", &err.to_string()))?;
+            if keep.is_true() {
+                kept.push(item);
+            }
+        }
+        values = kept;
+    }
+    if let Some(path) = unique_by {
+        let mut seen = BTreeSet::new();
+        values.retain(|item| seen.insert(lookup_path(item, &path)));
+    }
+    if let Some(path) = sort_by {
+        values.sort_by_cached_key(|item| lookup_path(item, &path));
+    }
+    if reverse {
+        values.reverse();
+    }
+    if let Some(seed) = seed {
+        shuffle(&mut values, seed);
+    }
+    let values: Vec<Value> = values
+        .into_iter()
+        .skip(offset)
+        .step_by(step)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    match chunk {
+        Some(size) => Ok(Value::from_iter(
+            values.chunks(size).map(|chunk| Value::from(chunk.to_vec())),
+        )),
+        None => Ok(Value::from(values)),
+    }
+}
+
+/// A number passed to [`mathematical_fold`].
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn from_arg(idx: usize, value: &Value) -> Result<Number, Error> {
+        if value.is_integer() {
+            i128::try_from(value.clone()).map(Number::Int)
+        } else if value.is_number() {
+            f64::try_from(value.clone()).map(Number::Float)
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!(
+                    "mathematical_fold: argument {} is a {}, expected a number",
+                    idx + 1,
+                    value.kind()
+                ),
+            ))
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(n) => n as f64,
+            Number::Float(f) => f,
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Value {
+        match number {
+            Number::Int(n) => Value::from(n),
+            Number::Float(f) => Value::from(f),
+        }
+    }
+}
+
+/// The operations [`mathematical_fold`] understands.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FoldOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    Avg,
+}
+
+impl fmt::Display for FoldOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FoldOp::Add => "add",
+            FoldOp::Sub => "sub",
+            FoldOp::Mul => "mul",
+            FoldOp::Div => "div",
+            FoldOp::Min => "min",
+            FoldOp::Max => "max",
+            FoldOp::Avg => "avg",
+        })
+    }
+}
+
+/// The keyword arguments of [`mathematical_fold`].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FoldOptions {
+    op: FoldOp,
+}
+
+fn fold_step(op: FoldOp, a: Number, b: Number) -> Result<Number, Error> {
+    let overflow = || {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("mathematical_fold: integer overflow in {op}"),
+        )
+    };
+    Ok(match (a, b) {
+        (Number::Int(a), Number::Int(b)) => Number::Int(match op {
+            FoldOp::Add | FoldOp::Avg => a.checked_add(b).ok_or_else(overflow)?,
+            FoldOp::Sub => a.checked_sub(b).ok_or_else(overflow)?,
+            FoldOp::Mul => a.checked_mul(b).ok_or_else(overflow)?,
+            FoldOp::Min => a.min(b),
+            FoldOp::Max => a.max(b),
+            FoldOp::Div => return fold_step(op, Number::Float(a as f64), Number::Float(b as f64)),
+        }),
+        (a, b) => {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            Number::Float(match op {
+                FoldOp::Add | FoldOp::Avg => a + b,
+                FoldOp::Sub => a - b,
+                FoldOp::Mul => a * b,
+                FoldOp::Min => a.min(b),
+                FoldOp::Max => a.max(b),
+                FoldOp::Div if b == 0.0 => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidOperation,
+                        "mathematical_fold: division by zero",
+                    ));
+                }
+                FoldOp::Div => a / b,
+            })
+        }
+    })
+}
+
+/// Folds all positional arguments with the operation named by `op`: `add`,
+/// `sub`, `mul`, `div`, `min`, `max` or `avg`.
+///
+/// `add`, `sub`, `mul`, `min` and `max` keep integers as integers and switch
+/// to floats as soon as one argument is a float; `div` and `avg` always
+/// return floats.  Non-numeric arguments, integer overflow, division by zero
+/// and unknown operations are reported as errors.
+pub fn mathematical_fold(args: Rest<Value>, kwargs: Kwargs) -> Result<Value, Error> {
+    let FoldOptions { op } = from_kwargs("mathematical_fold", &kwargs)?;
+    let numbers = args
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| Number::from_arg(idx, value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some((&first, rest)) = numbers.split_first() else {
+        return match op {
+            FoldOp::Add => Ok(Value::from(0)),
+            FoldOp::Mul => Ok(Value::from(1)),
+            _ => Err(Error::new(
+                ErrorKind::MissingArgument,
+                format!("mathematical_fold: {op} needs at least one number"),
+            )),
+        };
+    };
+    let first = match op {
+        FoldOp::Div => Number::Float(first.as_f64()),
+        _ => first,
+    };
+    let rv = rest
+        .iter()
+        .try_fold(first, |acc, &n| fold_step(op, acc, n))?;
+    Ok(match op {
+        FoldOp::Avg => Number::Float(rv.as_f64() / numbers.len() as f64),
+        _ => rv,
+    }
+    .into())
+}
+
+/// The `reduce(items, fn, initial=..., numeric=...)` global function.
+///
+/// Calls `fn(accumulator, item)` for each item and returns the last result.
+/// Without `initial` the first item starts the accumulator.  `fn` can be any
+/// callable, including a template macro.  Macros can only return text, so
+/// with `numeric=true` text results that parse as numbers are turned back
+/// into numbers.  `numeric` defaults to whether `initial` is a number, which
+/// makes `reduce(orders, sum_price, initial=0)` sum numbers rather than
+/// strings.  Alternatively convert inside the macro with `| int` or
+/// `| float`.
+pub fn reduce(
+    state: &State,
+    items: &Value,
+    callable: &Value,
+    kwargs: Kwargs,
+) -> Result<Value, Error> {
+    // Not `from_kwargs`: deserializing `initial` would turn objects such as
+    // points into plain maps.
+    let initial: Option<Value> = kwargs.get("initial")?;
+    let numeric: Option<bool> = kwargs.get("numeric")?;
+    kwargs.assert_all_used()?;
+    let numeric = numeric.unwrap_or_else(|| {
+        initial
+            .as_ref()
+            .is_some_and(|initial| initial.kind() == ValueKind::Number)
+    });
+    let mut items = items.try_iter()?;
+    let Some(mut acc) = initial.or_else(|| items.next()) else {
+        return Err(Error::new(
+            ErrorKind::InvalidOperation,
+            "reduce() of an empty sequence needs an initial value",
+        ));
+    };
+    for item in items {
+        acc = callable.call(state, &[acc, item])?;
+        if numeric {
+            acc = number_from_text(acc);
+        }
+    }
+    Ok(acc)
+}
+
+/// Parses macro output such as `" 42 "` back into a number.
+fn number_from_text(value: Value) -> Value {
+    let Some(text) = value.as_str().map(str::trim) else {
+        return value;
+    };
+    if let Ok(n) = text.parse::<i64>() {
+        Value::from(n)
+    } else if let Ok(f) = text.parse::<f64>()
+        && f.is_finite()
+    {
+        Value::from(f)
+    } else {
+        value
+    }
+}
@@ -0,0 +1,282 @@
+//! Shapes built from [`Point`]s: [`Polygon`], [`Path`] and [`BoundingBox`].
+//!
+//! Polygons and paths behave like sequences of points in templates, so
+//! `{% for p in poly %}` and `poly | length` work as expected.  Areas and
+//! lengths are computed in the xy plane for polygons and in 3D for paths.
+
+use crate::point::{Point, point_arg};
+use minijinja::value::{Enumerator, Object, ObjectRepr, Value, from_args};
+use minijinja::{Error, ErrorKind, State};
+use std::sync::Arc;
+
+/// Converts a sequence of point-like values into points.
+fn points_from_value(what: &str, value: &Value) -> Result<Vec<Point>, Error> {
+    let invalid = |detail: String| Error::new(ErrorKind::InvalidOperation, detail);
+    value
+        .try_iter()
+        .map_err(|_| {
+            invalid(format!(
+                "{what}() expects a sequence of points, got {}",
+                value.kind()
+            ))
+        })?
+        .enumerate()
+        .map(|(idx, item)| {
+            Point::from_value(&item)
+                .ok_or_else(|| invalid(format!("{what}(): item {idx} is not a point")))
+        })
+        .collect()
+}
+
+fn point_at(points: &[Point], key: &Value) -> Option<Value> {
+    let idx = key.as_usize()?;
+    points.get(idx).copied().map(Value::from_object)
+}
+
+fn no_args(args: &[Value]) -> Result<(), Error> {
+    from_args::<()>(args)
+}
+
+/// The bounding box of `points`, failing like the `bbox()` global for an
+/// empty list.
+fn bbox_of(points: &[Point]) -> Result<Value, Error> {
+    BoundingBox::from_points(points)
+        .map(Value::from_object)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                "bbox() needs at least one point",
+            )
+        })
+}
+
+fn distance_along(points: &[Point]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| pair[0].distance(&pair[1]))
+        .sum()
+}
+
+/// A closed polygon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon(pub Vec<Point>);
+
+impl Polygon {
+    /// The area enclosed in the xy plane (shoelace formula).
+    pub fn area(&self) -> f64 {
+        let n = self.0.len();
+        let twice_area: f64 = (0..n)
+            .map(|i| {
+                let (a, b) = (self.0[i], self.0[(i + 1) % n]);
+                f64::from(a.0) * f64::from(b.1) - f64::from(b.0) * f64::from(a.1)
+            })
+            .sum();
+        twice_area.abs() / 2.0
+    }
+
+    /// The length of the boundary including the closing edge.
+    pub fn perimeter(&self) -> f64 {
+        match (self.0.first(), self.0.last()) {
+            (Some(first), Some(last)) => distance_along(&self.0) + last.distance(first),
+            _ => 0.0,
+        }
+    }
+
+    /// The average of the vertices.
+    pub fn centroid(&self) -> Option<Point> {
+        let n = self.0.len() as f32;
+        (!self.0.is_empty()).then(|| {
+            let sum = self.0.iter().fold(Point(0.0, 0.0, 0.0), |acc, p| {
+                Point(acc.0 + p.0, acc.1 + p.1, acc.2 + p.2)
+            });
+            sum.scale(1.0 / n)
+        })
+    }
+}
+
+impl Object for Polygon {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Seq
+    }
+
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        point_at(&self.0, key)
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Seq(self.0.len())
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &State<'_, '_>,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        // Check the arguments before doing any work.
+        let method: fn(&Polygon) -> Result<Value, Error> = match method {
+            "area" => |polygon| Ok(Value::from(polygon.area())),
+            "perimeter" => |polygon| Ok(Value::from(polygon.perimeter())),
+            "centroid" => |polygon| {
+                polygon.centroid().map(Value::from_object).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidOperation,
+                        "centroid() needs at least one point",
+                    )
+                })
+            },
+            "bbox" => |polygon| bbox_of(&polygon.0),
+            _ => return Err(Error::from(ErrorKind::UnknownMethod)),
+        };
+        no_args(args)?;
+        method(self)
+    }
+}
+
+/// An open path through a list of points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path(pub Vec<Point>);
+
+impl Path {
+    /// The total length of all segments.
+    pub fn length(&self) -> f64 {
+        distance_along(&self.0)
+    }
+}
+
+impl Object for Path {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Seq
+    }
+
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        point_at(&self.0, key)
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Seq(self.0.len())
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &State<'_, '_>,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        // Check the arguments before doing any work.
+        let method: fn(&Path) -> Result<Value, Error> = match method {
+            "length" => |path| Ok(Value::from(path.length())),
+            "closed" => |path| Ok(Value::from_object(Polygon(path.0.clone()))),
+            "bbox" => |path| bbox_of(&path.0),
+            _ => return Err(Error::from(ErrorKind::UnknownMethod)),
+        };
+        no_args(args)?;
+        method(self)
+    }
+}
+
+/// An axis-aligned box spanning `min` to `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// The smallest box containing all `points`, or `None` if there are none.
+    pub fn from_points(points: &[Point]) -> Option<BoundingBox> {
+        let first = *points.first()?;
+        Some(points.iter().fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |bbox, p| BoundingBox {
+                min: Point(
+                    bbox.min.0.min(p.0),
+                    bbox.min.1.min(p.1),
+                    bbox.min.2.min(p.2),
+                ),
+                max: Point(
+                    bbox.max.0.max(p.0),
+                    bbox.max.1.max(p.1),
+                    bbox.max.2.max(p.2),
+                ),
+            },
+        ))
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.0 - self.min.0
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.1 - self.min.1
+    }
+
+    pub fn depth(&self) -> f32 {
+        self.max.2 - self.min.2
+    }
+
+    /// The area of the box's footprint in the xy plane.
+    pub fn area(&self) -> f64 {
+        f64::from(self.width()) * f64::from(self.height())
+    }
+
+    /// Whether `p` lies inside the box or on its boundary.
+    pub fn contains(&self, p: &Point) -> bool {
+        (self.min.0..=self.max.0).contains(&p.0)
+            && (self.min.1..=self.max.1).contains(&p.1)
+            && (self.min.2..=self.max.2).contains(&p.2)
+    }
+}
+
+impl Object for BoundingBox {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        match key.as_str()? {
+            "min" => Some(Value::from_object(self.min)),
+            "max" => Some(Value::from_object(self.max)),
+            "width" => Some(Value::from(self.width())),
+            "height" => Some(Value::from(self.height())),
+            "depth" => Some(Value::from(self.depth())),
+            _ => None,
+        }
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Str(&["min", "max", "width", "height", "depth"])
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &State<'_, '_>,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        match method {
+            "contains" => Ok(Value::from(self.contains(&point_arg(method, args)?))),
+            "area" => {
+                no_args(args)?;
+                Ok(Value::from(self.area()))
+            }
+            _ => Err(Error::from(ErrorKind::UnknownMethod)),
+        }
+    }
+}
+
+/// The `polygon(points)` global function.
+pub fn polygon(points: &Value) -> Result<Value, Error> {
+    Ok(Value::from_object(Polygon(points_from_value(
+        "polygon", points,
+    )?)))
+}
+
+/// The `path(points)` global function.
+pub fn path(points: &Value) -> Result<Value, Error> {
+    Ok(Value::from_object(Path(points_from_value("path", points)?)))
+}
+
+/// The `bbox(points)` global function.
+pub fn bbox(points: &Value) -> Result<Value, Error> {
+    bbox_of(&points_from_value("bbox", points)?)
+}
@@ -0,0 +1,331 @@
+//! Functions that show what a template can see while it is rendered.
+//!
+//! `context_info()` returns the template name, the current block, the
+//! auto-escape mode, the templates loaded so far and the type of every
+//! visible variable as a map.  `debug()` formats the same information as a
+//! text or HTML table.
+//!
+//! minijinja keeps the chain of `include`s private, so the include stack is
+//! rebuilt from a loader installed with [`track_includes`].  It records
+//! which template each template was loaded for: the most recently loaded
+//! template that names it in an `include`, `extends`, `import` or `from`
+//! tag, or else the most recent one with a computed name.  Templates are
+//! only loaded once per environment, so the stack shows who loaded a
+//! template first, which may be an earlier render.  Without
+//! `track_includes` the stack is `none`.
+
+use crate::commands::watch::{Dependencies, dependencies};
+use crate::geometry::{BoundingBox, Path, Polygon};
+use crate::kwargs::from_kwargs;
+use crate::point::Point;
+use minijinja::value::{Kwargs, Object, ObjectRepr, Value, ValueKind};
+use minijinja::{AutoEscape, Environment, Error, HtmlEscape, State};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Values longer than this are cut off in `debug()` tables.
+const MAX_VALUE_WIDTH: usize = 60;
+
+/// The global [`track_includes`] stores its [`IncludeLog`] in.
+const INCLUDE_LOG: &str = "__include_log";
+
+/// Which template each template was loaded for, see [`track_includes`].
+#[derive(Debug, Default)]
+pub struct IncludeLog {
+    loads: Mutex<Vec<Load>>,
+}
+
+#[derive(Debug)]
+struct Load {
+    name: String,
+    loaded_for: Option<String>,
+    dependencies: Dependencies,
+}
+
+impl IncludeLog {
+    fn record(&self, name: &str, source: &str) {
+        let mut loads = self.loads.lock().unwrap();
+        let loaded_for = loads
+            .iter()
+            .rev()
+            .find(|load| load.dependencies.names.contains(name))
+            .or_else(|| loads.iter().rev().find(|load| load.dependencies.dynamic))
+            .map(|load| load.name.clone());
+        loads.push(Load {
+            name: name.to_string(),
+            loaded_for,
+            dependencies: dependencies(source),
+        });
+    }
+
+    /// The templates that led to `name`, outermost first and ending with
+    /// `name` itself.
+    pub fn stack(&self, name: &str) -> Vec<String> {
+        let loads = self.loads.lock().unwrap();
+        let mut rv = vec![name.to_string()];
+        while let Some(parent) = loads
+            .iter()
+            .find(|load| load.name == rv[rv.len() - 1])
+            .and_then(|load| load.loaded_for.clone())
+        {
+            // A template that includes itself ends the stack.
+            if rv.contains(&parent) {
+                break;
+            }
+            rv.push(parent);
+        }
+        rv.reverse();
+        rv
+    }
+}
+
+impl Object for IncludeLog {}
+
+/// Makes `loader` the loader of `env` and records which template loaded
+/// which, so that `context_info()` and `debug()` can show the include stack.
+pub fn track_includes<F>(env: &mut Environment<'_>, loader: F) -> Arc<IncludeLog>
+where
+    F: Fn(&str) -> Result<Option<String>, Error> + Send + Sync + 'static,
+{
+    let value = Value::from_object(IncludeLog::default());
+    let log = value.downcast_object::<IncludeLog>().unwrap();
+    env.add_global(INCLUDE_LOG, value);
+    let recorder = log.clone();
+    env.set_loader(move |name| {
+        let source = loader(name)?;
+        if let Some(source) = &source {
+            recorder.record(name, source);
+        }
+        Ok(source)
+    });
+    log
+}
+
+/// A snapshot of what [`State`] knows about the current render.
+struct ContextInfo {
+    template: String,
+    block: Option<String>,
+    auto_escape: String,
+    templates: Vec<String>,
+    /// Outermost first, if includes are tracked.
+    include_stack: Option<Vec<String>>,
+    /// `(type, value)` of each visible variable.
+    variables: BTreeMap<String, (String, Value)>,
+}
+
+impl ContextInfo {
+    fn new(state: &State) -> ContextInfo {
+        let env = state.env();
+        let globals: BTreeMap<&str, Value> = env.globals().collect();
+        let variables = state
+            .known_variables()
+            .into_iter()
+            .filter_map(|name| {
+                let value = state.lookup(&name)?;
+                // Globals are the same in every template; only list them if
+                // the context shadows them.
+                if globals.get(&*name) == Some(&value) {
+                    return None;
+                }
+                Some((name.into_owned(), (type_name(&value), value)))
+            })
+            .collect();
+        ContextInfo {
+            template: state.name().to_string(),
+            block: state.current_block().map(str::to_string),
+            auto_escape: auto_escape_name(state.auto_escape()),
+            templates: env
+                .templates()
+                .map(|(name, _)| name.to_string())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            include_stack: globals
+                .get(INCLUDE_LOG)
+                .and_then(|log| log.downcast_object_ref::<IncludeLog>())
+                .map(|log| log.stack(state.name())),
+            variables,
+        }
+    }
+
+    /// The rows above the variable table.
+    fn summary(&self) -> [(&'static str, String); 5] {
+        [
+            ("template", self.template.clone()),
+            ("block", self.block.clone().unwrap_or_else(|| "-".into())),
+            ("auto-escape", self.auto_escape.clone()),
+            ("templates", self.templates.join(", ")),
+            (
+                "include stack",
+                self.include_stack
+                    .as_ref()
+                    .map_or_else(|| "-".into(), |stack| stack.join(" > ")),
+            ),
+        ]
+    }
+
+    fn to_value(&self) -> Value {
+        let variables: BTreeMap<&str, &str> = self
+            .variables
+            .iter()
+            .map(|(name, (kind, _))| (name.as_str(), kind.as_str()))
+            .collect();
+        Value::from_iter([
+            ("template", Value::from(self.template.as_str())),
+            ("block", Value::from(self.block.as_deref())),
+            ("auto_escape", Value::from(self.auto_escape.as_str())),
+            ("templates", Value::from(self.templates.clone())),
+            ("include_stack", Value::from(self.include_stack.clone())),
+            ("variables", Value::from_serialize(variables)),
+        ])
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "{label}: {value}");
+        }
+        let rows: Vec<[String; 3]> = self
+            .variables
+            .iter()
+            .map(|(name, (kind, value))| [name.clone(), kind.clone(), short_repr(value)])
+            .collect();
+        let header = ["variable", "type", "value"].map(String::from);
+        let width = |col: usize| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let (name_width, type_width) = (width(0), width(1));
+        for row in [header].iter().chain(&rows) {
+            let line = format!("{:name_width$}  {:type_width$}  {}", row[0], row[1], row[2]);
+            let _ = write!(out, "\n{}", line.trim_end());
+        }
+        out
+    }
+
+    fn to_html(&self) -> String {
+        let mut out = String::from("<table class=\"context-info\">\n");
+        for (label, value) in self.summary() {
+            let _ = writeln!(
+                out,
+                "<tr><th>{label}</th><td colspan=\"2\">{}</td></tr>",
+                HtmlEscape(&value)
+            );
+        }
+        out.push_str("<tr><th>variable</th><th>type</th><th>value</th></tr>\n");
+        for (name, (kind, value)) in &self.variables {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td><code>{}</code></td></tr>",
+                HtmlEscape(name),
+                HtmlEscape(kind),
+                HtmlEscape(&short_repr(value))
+            );
+        }
+        out.push_str("</table>");
+        out
+    }
+}
+
+/// The type shown for `value`: the crate's own objects by name, everything
+/// else by its kind.
+fn type_name(value: &Value) -> String {
+    if value.downcast_object_ref::<Point>().is_some() {
+        "point".into()
+    } else if value.downcast_object_ref::<Polygon>().is_some() {
+        "polygon".into()
+    } else if value.downcast_object_ref::<Path>().is_some() {
+        "path".into()
+    } else if value.downcast_object_ref::<BoundingBox>().is_some() {
+        "bbox".into()
+    } else if is_macro(value) {
+        "macro".into()
+    } else {
+        value.kind().to_string()
+    }
+}
+
+/// Whether `value` is a macro defined in a template.
+fn is_macro(value: &Value) -> bool {
+    // minijinja's macro type is private.  Macros are objects with exactly
+    // the keys `name`, `arguments` and `caller`; only values with that shape
+    // are formatted to confirm it, which is cheap for a macro.
+    let Some(obj) = value.as_object() else {
+        return false;
+    };
+    let has = |key: &str, kind: ValueKind| {
+        obj.get_value(&Value::from(key))
+            .is_some_and(|value| value.kind() == kind)
+    };
+    obj.repr() == ObjectRepr::Map
+        && obj.enumerator_len() == Some(3)
+        && has("name", ValueKind::String)
+        && has("arguments", ValueKind::Seq)
+        && has("caller", ValueKind::Bool)
+        && format!("{value:?}").starts_with("<macro ")
+}
+
+fn auto_escape_name(auto_escape: AutoEscape) -> String {
+    match auto_escape {
+        AutoEscape::None => "none".into(),
+        AutoEscape::Html => "html".into(),
+        AutoEscape::Json => "json".into(),
+        AutoEscape::Custom(name) => name.into(),
+        other => format!("{other:?}").to_lowercase(),
+    }
+}
+
+fn short_repr(value: &Value) -> String {
+    let repr = format!("{value:?}");
+    match repr.char_indices().nth(MAX_VALUE_WIDTH) {
+        Some((idx, _)) => format!("{}...", &repr[..idx]),
+        None => repr,
+    }
+}
+
+/// The `context_info()` global function.
+///
+/// Returns a map with `template`, `block`, `auto_escape`, `templates`,
+/// `include_stack` and `variables`, which maps each visible variable to its
+/// type.  See the module docs for how the include stack is found.
+pub fn context_info(state: &State) -> Value {
+    ContextInfo::new(state).to_value()
+}
+
+/// The keyword arguments of [`debug`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DebugOptions {
+    format: Option<DebugFormat>,
+}
+
+/// The formats [`debug`] can produce.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DebugFormat {
+    Html,
+    Text,
+}
+
+/// The `debug()` global function.
+///
+/// Dumps the same information as `context_info()` plus a preview of each
+/// variable's value.  `format` is `"html"` (a `<table>`) or `"text"`; it
+/// defaults to HTML in auto-escaped HTML templates and text elsewhere.
+pub fn debug(state: &State, kwargs: Kwargs) -> Result<Value, Error> {
+    let DebugOptions { format } = from_kwargs("debug", &kwargs)?;
+    let info = ContextInfo::new(state);
+    let format = format.unwrap_or(match state.auto_escape() {
+        AutoEscape::Html => DebugFormat::Html,
+        _ => DebugFormat::Text,
+    });
+    Ok(match format {
+        DebugFormat::Html => Value::from_safe_string(info.to_html()),
+        DebugFormat::Text => Value::from(info.to_text()),
+    })
+}
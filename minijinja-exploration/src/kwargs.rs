@@ -0,0 +1,73 @@
+//! Typed keyword arguments.
+//!
+//! Instead of pulling each option out of [`Kwargs`] by hand, a function can
+//! describe its options as a struct and let serde do the checking:
+//!
+//! ```
+//! use minijinja::Error;
+//! use minijinja::value::{Kwargs, Value};
+//! use minijinja_exploration::kwargs::from_kwargs;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Default)]
+//! #[serde(default, deny_unknown_fields)]
+//! struct Options {
+//!     reverse: bool,
+//!     limit: Option<usize>,
+//! }
+//!
+//! fn modify(mut values: Vec<Value>, kwargs: Kwargs) -> Result<Vec<Value>, Error> {
+//!     let options: Options = from_kwargs("modify", &kwargs)?;
+//!     if options.reverse {
+//!         values.reverse();
+//!     }
+//!     values.truncate(options.limit.unwrap_or(usize::MAX));
+//!     Ok(values)
+//! }
+//! ```
+//!
+//! `#[serde(default)]` fills in missing options and `deny_unknown_fields`
+//! turns misspelled ones into errors.
+
+use minijinja::value::{Kwargs, Value};
+use minijinja::{Error, ErrorKind};
+use serde::de::DeserializeOwned;
+
+/// Deserializes `kwargs` into `T`.
+///
+/// Errors name `function` and the offending keyword argument.  Unknown
+/// arguments are reported as [`ErrorKind::TooManyArguments`] and missing
+/// ones as [`ErrorKind::MissingArgument`], like minijinja does for
+/// positional arguments; everything else is an
+/// [`ErrorKind::InvalidOperation`].
+pub fn from_kwargs<T: DeserializeOwned>(function: &str, kwargs: &Kwargs) -> Result<T, Error> {
+    let value = kwargs
+        .args()
+        .map(|name| Ok((name, kwargs.get::<Value>(name)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    serde_path_to_error::deserialize(Value::from_iter(value)).map_err(|err| {
+        let depth = err.path().iter().count();
+        let path = err.path().to_string();
+        let err = err.into_inner();
+        let detail = err.detail().unwrap_or("invalid keyword arguments");
+        // serde reports these through `Error::custom`, so the message is all
+        // there is to go by; `errors_name_the_argument` in tests/kwargs.rs
+        // pins the wording.  The path of an unknown field ends in the field
+        // itself while a missing field is reported at the struct containing
+        // it, so only these depths are keyword arguments of the call.
+        let kind = if detail.starts_with("unknown field `") && depth <= 1 {
+            ErrorKind::TooManyArguments
+        } else if detail.starts_with("missing field `") && depth == 0 {
+            ErrorKind::MissingArgument
+        } else {
+            ErrorKind::InvalidOperation
+        };
+        let message = match kind {
+            ErrorKind::InvalidOperation if path != "." => {
+                format!("{function}: invalid value for `{path}`: {detail}")
+            }
+            _ => format!("{function}: {detail}"),
+        };
+        Error::new(kind, message)
+    })
+}
@@ -0,0 +1,30 @@
+//! Implementation of the code examples in the minijinja documentation.
+
+use minijinja::Environment;
+
+pub mod commands;
+pub mod embedded;
+pub mod examples;
+pub mod filters;
+pub mod functions;
+pub mod geometry;
+pub mod introspection;
+pub mod kwargs;
+pub mod limits;
+pub mod point;
+pub mod registry;
+#[cfg(unix)]
+pub mod sandbox;
+pub mod svg;
+pub mod theme;
+
+/// Registers every custom filter and function of this crate on `env`, see
+/// [`registry::ENTRIES`].
+///
+/// `repeat` is bounded by [`limits::DEFAULT_MAX_OUTPUT`]; use
+/// [`limits::Limits::apply`] to configure fuel and a different budget.
+pub fn add_extensions(env: &mut Environment<'_>) {
+    for entry in registry::ENTRIES {
+        entry.register(env);
+    }
+}
@@ -0,0 +1,135 @@
+//! Resource limits for rendering templates.
+//!
+//! [`Limits`] bundles the fuel limit and the output budget so both are
+//! configured in one place.  Fuel bounds the number of instructions a render
+//! may execute, which stops endless loops; the output budget bounds the size
+//! of the rendered output and of every string the `repeat` filter builds.
+//!
+//! `Limits` is not a safety boundary on its own:
+//!
+//! - The output budget only applies to renders that go through
+//!   [`Limits::render`] or [`Limits::render_to_write`].  [`Limits::apply`]
+//!   can't hook into `Template::render`, which ignores it.
+//! - Strings and lists minijinja builds itself, such as `'x' * 10000000000`
+//!   or `s ~ s`, are not bounded.  They can exhaust memory, even while a
+//!   template is compiled, and a failed allocation aborts the process.
+//!
+//! Use [`crate::sandbox`] to render templates that can't be trusted.
+
+use minijinja::value::Value;
+use minijinja::{Environment, Error, ErrorKind, Template};
+use std::io;
+
+/// The fuel a render gets by default.
+pub const DEFAULT_FUEL: u64 = 100_000;
+
+/// The output budget in bytes a render gets by default.
+pub const DEFAULT_MAX_OUTPUT: usize = 1024 * 1024;
+
+/// How much a single render may consume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Fuel per render, see [`Environment::set_fuel`].  `None` disables the
+    /// limit.
+    pub fuel: Option<u64>,
+    /// The largest output in bytes a render, or a single `repeat`, may
+    /// produce.
+    pub max_output: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            fuel: Some(DEFAULT_FUEL),
+            max_output: DEFAULT_MAX_OUTPUT,
+        }
+    }
+}
+
+impl Limits {
+    /// Sets the fuel limit on `env` and registers a `repeat` filter bounded
+    /// by the output budget.  The budget of the whole output is only
+    /// enforced by [`render`](Self::render) and
+    /// [`render_to_write`](Self::render_to_write).
+    pub fn apply(&self, env: &mut Environment<'_>) {
+        env.set_fuel(self.fuel);
+        let max_output = self.max_output;
+        env.add_filter("repeat", move |value: &str, n: usize| {
+            repeat(value, n, max_output)
+        });
+    }
+
+    /// Renders `tmpl`, failing as soon as the output exceeds the budget.
+    pub fn render(&self, tmpl: &Template<'_, '_>, ctx: Value) -> Result<String, Error> {
+        let mut out = Vec::new();
+        self.render_to_write(tmpl, ctx, &mut out)?;
+        String::from_utf8(out).map_err(|err| {
+            Error::new(
+                ErrorKind::BadSerialization,
+                "template produced invalid UTF-8",
+            )
+            .with_source(err)
+        })
+    }
+
+    /// Renders `tmpl` into `out`, failing as soon as the output exceeds the
+    /// budget.  Output written before that point is not taken back.
+    pub fn render_to_write<W: io::Write>(
+        &self,
+        tmpl: &Template<'_, '_>,
+        ctx: Value,
+        out: W,
+    ) -> Result<(), Error> {
+        let mut out = Budget {
+            out,
+            remaining: self.max_output,
+            exceeded: false,
+        };
+        match tmpl.render_to_write(ctx, &mut out) {
+            Err(_) if out.exceeded => Err(over_budget("the rendered output", self.max_output)),
+            rv => rv.map(|_| ()),
+        }
+    }
+}
+
+fn over_budget(what: &str, max_output: usize) -> Error {
+    Error::new(
+        ErrorKind::InvalidOperation,
+        format!("{what} exceeds the output limit of {max_output} bytes"),
+    )
+}
+
+/// A writer that refuses to write more than `remaining` bytes.
+struct Budget<W> {
+    out: W,
+    remaining: usize,
+    exceeded: bool,
+}
+
+impl<W: io::Write> io::Write for Budget<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining {
+            self.exceeded = true;
+            return Err(io::Error::other("output limit exceeded"));
+        }
+        let written = self.out.write(buf)?;
+        self.remaining -= written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// The `repeat` filter: `value` repeated `n` times, as long as the result
+/// stays within `max_len` bytes.
+pub fn repeat(value: &str, n: usize, max_len: usize) -> Result<String, Error> {
+    match value.len().checked_mul(n) {
+        Some(len) if len <= max_len => Ok(value.repeat(n)),
+        _ => Err(over_budget(
+            &format!("repeating a string of {} bytes {n} times", value.len()),
+            max_len,
+        )),
+    }
+}
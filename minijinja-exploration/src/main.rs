@@ -1,264 +1,141 @@
-use minijinja::value::{Enumerator, Kwargs, Object, Rest, Value, from_args};
-use minijinja::{Environment, context};
-use minijinja::{Error, State};
-use std::io::stdout;
-use std::{collections::HashSet, sync::Arc};
-
-fn test_template_usage() {
-    let mut env = Environment::new();
-    env.add_template("hello", "Hello {{ name }}!").unwrap();
-    let tmpl = env.get_template("hello").unwrap();
-    println!("{}", tmpl.render(context!(name => "John")).unwrap());
-}
-
-fn test_expression_usage() {
-    let env = Environment::new();
-    let expr = env.compile_expression("number < 42").unwrap();
-    let result = expr.eval(context! (number => 23)).unwrap();
-    assert_eq!(result.is_true(), true);
-}
-
-// Dynamic objects
-#[derive(Debug)]
-struct Point(f32, f32, f32);
-
-impl Object for Point {
-    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
-        match key.as_str()? {
-            "x" => Some(Value::from(self.0)),
-            "y" => Some(Value::from(self.1)),
-            "z" => Some(Value::from(self.2)),
-            _ => None,
+use minijinja_exploration::commands::{self, CommandResult, UsageError};
+use minijinja_exploration::examples::{self, Example};
+use std::any::Any;
+use std::io::{self, Write};
+use std::panic;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: minijinja-exploration [COMMAND]
+
+Commands:
+  list              List the registered examples
+  list-filters      Document the custom filters and functions
+  run [PATTERN...]  Run the examples matching the glob patterns (default: all)
+  render            Render a template file with JSON context files
+  repl              Evaluate expressions interactively
+  lint              Check template variables against a context schema
+  watch             Re-render templates whenever they change
+  dump              Show the AST and instructions of a template
+  svg               Render points and shapes from JSON into an SVG document
+  help              Show this message";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run(&[]),
+        Some("list") => list(),
+        Some("run") => run(&args[1..]),
+        Some("list-filters") => report(
+            commands::list_filters::run(&args[1..]),
+            commands::list_filters::USAGE,
+        ),
+        Some("render") => report(commands::render::run(&args[1..]), commands::render::USAGE),
+        Some("dump") => report(commands::dump::run(&args[1..]), commands::dump::USAGE),
+        Some("lint") => report(commands::lint::run(&args[1..]), commands::lint::USAGE),
+        Some("repl") => report(commands::repl::run(&args[1..]), commands::repl::USAGE),
+        Some("svg") => report(commands::svg::run(&args[1..]), commands::svg::USAGE),
+        Some("watch") => report(commands::watch::run(&args[1..]), commands::watch::USAGE),
+        Some("help" | "-h" | "--help") => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Some(other) => {
+            eprintln!("error: unknown command `{other}`\n\n{USAGE}");
+            ExitCode::from(2)
         }
-    }
-
-    fn enumerate(self: &Arc<Self>) -> Enumerator {
-        Enumerator::Str(&["x", "y", "z"])
     }
 }
 
-fn test_dynamic_objects() {
-    let value = Value::from_object(Point(1.0, 2.5, 3.0));
-    if let Some(object) = value.as_object() {
-        assert_eq!(object.get_value(&Value::from("x")), Some(Value::from(1.0)));
-        assert_eq!(object.get_value(&Value::from("y")), Some(Value::from(2.5)));
-        assert_eq!(object.get_value(&Value::from("z")), Some(Value::from(3.0)));
+/// Turns the outcome of a subcommand into an exit code, printing its usage
+/// text for command line errors.
+fn report(result: CommandResult, usage: &str) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.is::<UsageError>() => {
+            eprintln!("error: {err}\n\n{usage}");
+            ExitCode::from(2)
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
     }
 }
 
-// Custom filters
-fn test_custom_filters() {
-    let mut env = Environment::new();
-    env.add_filter("repeat", str::repeat);
-    env.add_template("hello", "{{ 'Na ' | repeat(3) }} {{ name }}!")
-        .unwrap();
-    let tmpl = env.get_template("hello").unwrap();
-    println!("{}", tmpl.render(context! (name => "Batman")).unwrap());
-}
-
-// Environment related
-// How to iterate through templates in an environment
-fn test_templates_iteration() {
-    let mut env = Environment::new();
-    env.add_template("hello.txt", "Hello {{ name }}!").unwrap();
-    env.add_template("goodbye.txt", "Goodbye {{ name }}!")
-        .unwrap();
-
-    for (name, tmpl) in env.templates() {
-        println!(
-            "The template {} renders to {}",
-            name,
-            tmpl.render(context! {name => "World"}).unwrap()
-        );
+fn list() -> ExitCode {
+    let width = examples::EXAMPLES
+        .iter()
+        .map(|example| example.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = io::stdout().lock();
+    let written = examples::EXAMPLES
+        .iter()
+        .try_for_each(|example| writeln!(out, "{:width$}  {}", example.name, example.description));
+    match written {
+        // The reader went away, e.g. `list | head`.
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+        _ => ExitCode::SUCCESS,
     }
 }
 
-// Getting a template by name
-fn test_get_template_by_name() {
-    let mut env = Environment::new();
-    env.add_template("hello.txt", "Hello {{ name }} !").unwrap();
-    let tmpl = env.get_template("hello.txt").unwrap();
-    println!("{}", tmpl.render(context! { name => "World" }).unwrap());
-}
-
-// Loading a template from a string
-fn test_loading_template_from_a_string() {
-    let env = Environment::new();
-    let tmpl = env
-        .template_from_named_str("template_name", "Hello {{ name }}")
-        .unwrap();
-    let rv = tmpl.render(context! {name => "World"});
-    println!("{}", rv.unwrap());
-}
-
-// Parsing and rendering a template from a string in one go
-fn test_parse_and_render_from_string_in_one_go() {
-    let env = Environment::new();
-    let rv = env.render_named_str(
-        "template_name",
-        "Hello {{ name }}",
-        context! { name => "World" },
-    );
-    println!("{}", rv.unwrap());
-}
-
-// Rendering and returning the evaluated state
-fn test_render_and_return_evaluated_state() {
-    let env = Environment::new();
-    let tmpl = env
-        .template_from_str("{% set x = 42 %}Hello {{ what }}!")
-        .unwrap();
-    let (rv, state) = tmpl
-        .render_and_return_state(context! { what => "World"})
-        .unwrap();
-    assert_eq!(rv, "Hello World!");
-    assert_eq!(state.lookup("x"), Some(Value::from(42)));
-
-    // Render and send output to stdout
-    tmpl.render_to_write(context! { what => "John"}, &mut stdout())
-        .unwrap();
-}
-
-// Discard output and return internal state
-fn test_discard_output_and_return_internal_state() {
-    let mut env = Environment::new();
-    env.add_template("hello", "Hello {{ name }}!").unwrap();
-    let tmpl = env.get_template("hello").unwrap();
-    let state = tmpl.eval_to_state(context! { name => "John"}).unwrap();
-    println!("{:?}", state.exports());
-}
-
-// Returning undeclared variables in the template
-fn test_return_undeclared_variables() {
-    let mut env = Environment::new();
-    env.add_template("x", "{% set x = foo %}{{ x }}{{ bar.baz }}")
-        .unwrap();
-    let tmpl = env.get_template("x").unwrap();
-    let undeclared = tmpl.undeclared_variables(false);
-    assert_eq!(
-        undeclared,
-        HashSet::from(["foo".to_string(), "bar".to_string()])
-    );
-    let undeclared = tmpl.undeclared_variables(true);
-    assert_eq!(
-        undeclared,
-        HashSet::from(["foo".to_string(), "bar.baz".to_string()])
-    );
-}
-
-// Custom filters
-fn slugify(value: String) -> String {
-    value
-        .to_lowercase()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join("-")
-}
-
-fn append_template(state: &State, value: &Value) -> String {
-    format!("{}-{}", value, state.name())
-}
-
-fn test_custom_filters_example1_slugify() {
-    let mut env = Environment::new();
-    env.add_filter("slugify", slugify);
-
-    env.add_template("hello", "Hello {{ name | slugify }}!")
-        .unwrap();
-    let tmpl = env.get_template("hello").unwrap();
-    println!(
-        "{}",
-        tmpl.render(context!(name => "John Wild Oak")).unwrap()
-    );
-
-    env.add_filter("append_template", append_template);
-    env.add_template("state_of_the_template", "{{ name | append_template }}")
-        .unwrap();
-    let tmpl = env.get_template("state_of_the_template").unwrap();
-    println!(
-        "{}",
-        tmpl.render(context!(name => "John Wild Oak")).unwrap()
-    );
-}
-
-// Keyword arguments
-fn modify(mut values: Vec<Value>, options: Kwargs) -> Result<Vec<Value>, minijinja::Error> {
-    // Get pulls a parameter of any type. Same as from_args.
-    if let Some(true) = options.get("reverse")? {
-        values.reverse();
-    }
-    if let Some(limit) = options.get("limit")? {
-        values.truncate(limit);
+fn run(patterns: &[String]) -> ExitCode {
+    let mut selected: Vec<&Example> = Vec::new();
+    if patterns.is_empty() {
+        selected.extend(examples::EXAMPLES);
     }
-    // Extra unused keyword arguments will create an error
-    options.assert_all_used()?;
-    Ok(values)
-}
-
-fn mathematical_fold(in_args: Rest<Value>) -> Result<Value, Error> {
-    let (args, kwargs) = from_args::<(&[Value], Kwargs)>(&in_args)?;
-    let mut accum : i64= 1;
-    if let Some("mul") = kwargs.get("op")? {
-        
-        for val in args {
-            accum *= val.as_i64().unwrap();
+    for pattern in patterns {
+        let matches = examples::find(pattern);
+        if matches.is_empty() {
+            eprintln!("error: no example matches `{pattern}`");
+            return ExitCode::from(2);
         }
-    }
-    if let Some("add") = kwargs.get("op")? {
-        accum = 0;
-        for val in args {
-            accum += val.as_i64().unwrap();
+        for example in matches {
+            if !selected.iter().any(|e| e.name == example.name) {
+                selected.push(example);
+            }
         }
     }
-    Ok(Value::from(accum))
-}
 
-fn test_kwarg_handling() {
-    let mut env = Environment::new();
-    env.add_function("modify", modify);
-    env.add_template("mod_vec", "{{ modify(input_vec, limit=4, reverse=true) }}")
-        .unwrap();
-    let tmpl = env.get_template("mod_vec").unwrap();
-    let my_input_vec = vec![1, 2, 4, 5, 6, 7, 8, 9];
-    println!(
-        "{}",
-        tmpl.render(context! (input_vec => my_input_vec)).unwrap()
-    );
-
-    // How to create a Kwarg object from scratch
-    let kwargs_from_scratch =
-        Kwargs::from_iter([("foo", Value::from(true)), ("bar", Value::from(42))]);
-    let value_from_kwargs = Value::from(kwargs_from_scratch);
-    assert!(value_from_kwargs.is_kwargs());
+    // Assertion failures inside an example are reported as failures below,
+    // so the default panic message would only be noise.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut failed = 0;
+    for example in &selected {
+        println!("--- {}", example.name);
+        match panic::catch_unwind(|| (example.run)(&mut io::stdout().lock())) {
+            Ok(Ok(())) => println!("PASS {}", example.name),
+            Ok(Err(err)) => {
+                failed += 1;
+                println!("FAIL {}: {err:#}", example.name);
+            }
+            Err(payload) => {
+                failed += 1;
+                println!("FAIL {}: {}", example.name, panic_message(&*payload));
+            }
+        }
+    }
+    panic::set_hook(default_hook);
 
-    // How to use Rest to handle variadic parameters
-    env.add_function("mathematical_fold", mathematical_fold);
-    env.add_template("fold_mul", "{{ mathematical_fold(1,2,3,4, op = mul) }}").unwrap();
-    let tmpl_mul = env.get_template("fold_mul").unwrap();
-    println!(
-        "{}",
-        tmpl_mul.render(context! (mul => "mul")).unwrap()
-    );
-    env.add_template("fold_add", "{{ mathematical_fold(1,2,3,4, op = add) }}").unwrap();
-    let tmpl_add = env.get_template("fold_add").unwrap();
-    println!(
-        "{}",
-        tmpl_add.render(context! (add => "add")).unwrap()
-    );
+    println!("\n{} passed, {} failed", selected.len() - failed, failed);
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
 
-fn main() {
-    test_template_usage();
-    test_expression_usage();
-    test_dynamic_objects();
-    test_custom_filters();
-    test_templates_iteration();
-    test_get_template_by_name();
-    test_loading_template_from_a_string();
-    test_parse_and_render_from_string_in_one_go();
-    test_render_and_return_evaluated_state();
-    test_discard_output_and_return_internal_state();
-    test_return_undeclared_variables();
-    test_custom_filters_example1_slugify();
-    test_kwarg_handling();
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "panicked"
+    }
 }
@@ -0,0 +1,292 @@
+//! A 3D point exposed to templates as a dynamic object.
+
+use crate::kwargs::from_kwargs;
+use minijinja::value::{
+    DynObject, Enumerator, Kwargs, Object, ObjectRepr, Rest, Value, ValueKind, from_args,
+};
+use minijinja::{Error, ErrorKind, State};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+/// A point (or vector) with `x`, `y` and `z` coordinates.
+///
+/// Besides the three attributes, templates can call `length()`, `dot(q)`,
+/// `cross(q)`, `normalize()`, `scale(k)` and `distance(q)`.  Methods that
+/// produce a vector return a new `Point`.
+///
+/// Points compare by value, ordered by `x`, then `y`, then `z`, so they work
+/// with `sort`, `unique`, `==` and as map keys.  `-0.0` and `0.0` are equal,
+/// and the constructors and vector methods turn `-0.0` into `0.0` so it
+/// doesn't show up in the output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point(pub f32, pub f32, pub f32);
+
+impl Point {
+    /// Creates a point, replacing `-0.0` coordinates with `0.0`.
+    pub fn new(x: f32, y: f32, z: f32) -> Point {
+        // Adding `0.0` leaves every value alone except `-0.0`.
+        Point(x + 0.0, y + 0.0, z + 0.0)
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dot(&self, other: &Point) -> f64 {
+        f64::from(self.0) * f64::from(other.0)
+            + f64::from(self.1) * f64::from(other.1)
+            + f64::from(self.2) * f64::from(other.2)
+    }
+
+    pub fn cross(&self, other: &Point) -> Point {
+        Point::new(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+
+    pub fn scale(&self, factor: f32) -> Point {
+        Point::new(self.0 * factor, self.1 * factor, self.2 * factor)
+    }
+
+    /// The unit vector pointing the same way, or `None` for the origin.
+    pub fn normalize(&self) -> Option<Point> {
+        let length = self.length();
+        if length == 0.0 {
+            return None;
+        }
+        Some(self.scale((1.0 / length) as f32))
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        Point(self.0 - other.0, self.1 - other.1, self.2 - other.2).length()
+    }
+
+    /// Orders points lexicographically by `x`, `y` and `z`.
+    ///
+    /// `-0.0` and `0.0` compare equal.  Coordinates that are `NaN` fall back
+    /// to [`f32::total_cmp`], so every pair of points is ordered.
+    pub fn total_cmp(&self, other: &Point) -> Ordering {
+        let cmp = |a: f32, b: f32| a.partial_cmp(&b).unwrap_or_else(|| a.total_cmp(&b));
+        cmp(self.0, other.0)
+            .then(cmp(self.1, other.1))
+            .then(cmp(self.2, other.2))
+    }
+
+    /// Converts a `Point`, a map with exactly the numeric keys `x`, `y` and
+    /// `z`, or a sequence of exactly three numbers into a point.
+    pub fn from_value(value: &Value) -> Option<Point> {
+        if let Some(point) = value.downcast_object_ref::<Point>() {
+            return Some(*point);
+        }
+        let coord = |value: Value| value.is_number().then(|| f32::try_from(value).ok())?;
+        match value.kind() {
+            ValueKind::Map if value.len() == Some(3) => Some(Point::new(
+                coord(value.get_attr("x").ok()?)?,
+                coord(value.get_attr("y").ok()?)?,
+                coord(value.get_attr("z").ok()?)?,
+            )),
+            ValueKind::Seq if value.len() == Some(3) => Some(Point::new(
+                coord(value.get_item_by_index(0).ok()?)?,
+                coord(value.get_item_by_index(1).ok()?)?,
+                coord(value.get_item_by_index(2).ok()?)?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// The `point()` global function.
+///
+/// Called as `point(x, y, z)` with three numbers, or as `point(value)` with
+/// anything [`Point::from_value`] accepts.
+pub fn point(args: Rest<Value>) -> Result<Value, Error> {
+    let invalid = |detail: String| Error::new(ErrorKind::InvalidOperation, detail);
+    let point = match args.as_slice() {
+        [x, y, z] => {
+            let coord = |value: &Value| {
+                if value.is_number() {
+                    f32::try_from(value.clone())
+                } else {
+                    Err(invalid(format!(
+                        "point() coordinates must be numbers, got {}",
+                        value.kind()
+                    )))
+                }
+            };
+            Point::new(coord(x)?, coord(y)?, coord(z)?)
+        }
+        [value] => Point::from_value(value).ok_or_else(|| {
+            invalid(format!(
+                "cannot convert {} to a point, expected {{x, y, z}} or [x, y, z]",
+                value.kind()
+            ))
+        })?,
+        _ => {
+            return Err(invalid(format!(
+                "point() takes 3 coordinates or 1 value, got {} arguments",
+                args.len()
+            )));
+        }
+    };
+    Ok(Value::from_object(point))
+}
+
+/// Recursively replaces every map and sequence in `value` that
+/// [`Point::from_value`] accepts with a `Point`.
+///
+/// This is meant for contexts loaded from JSON, so points stored as
+/// `{"x": 1, "y": 2, "z": 3}` or `[1, 2, 3]` get the same methods and
+/// rendering as points created in Rust.
+pub fn convert_points(value: Value) -> Value {
+    if let Some(point) = Point::from_value(&value) {
+        return Value::from_object(point);
+    }
+    match value.as_object().map(|obj| obj.repr()) {
+        Some(ObjectRepr::Map) => Value::from_iter(
+            value
+                .try_iter()
+                .into_iter()
+                .flatten()
+                .map(|key| {
+                    let item = value.get_item(&key).unwrap_or_default();
+                    (key, convert_points(item))
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Some(ObjectRepr::Seq) => Value::from(
+            value
+                .try_iter()
+                .into_iter()
+                .flatten()
+                .map(convert_points)
+                .collect::<Vec<_>>(),
+        ),
+        _ => value,
+    }
+}
+
+/// Renders as `(x, y, z)` using the shortest representation of each
+/// coordinate, e.g. `(1, 2.5, 3)`.
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+/// The keyword arguments of [`format_point`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FormatOptions {
+    precision: Option<usize>,
+    style: PointStyle,
+}
+
+/// The layouts [`format_point`] can produce.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PointStyle {
+    #[default]
+    Tuple,
+    Json,
+}
+
+/// Formats a point with a fixed number of decimals and/or another style.
+///
+/// `style` is either `"tuple"` (the default, `(1.00, 2.50, 3.00)`) or
+/// `"json"` (`{"x": 1.00, "y": 2.50, "z": 3.00}`).  Without `precision`
+/// the shortest representation of each coordinate is used.
+pub fn format_point(value: &Value, kwargs: Kwargs) -> Result<String, Error> {
+    let point = value.downcast_object_ref::<Point>().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("format_point expects a point, got {}", value.kind()),
+        )
+    })?;
+    let FormatOptions { precision, style } = from_kwargs("format_point", &kwargs)?;
+
+    let coord = |c: f32| match precision {
+        Some(precision) => format!("{c:.precision$}"),
+        None => c.to_string(),
+    };
+    let (x, y, z) = (coord(point.0), coord(point.1), coord(point.2));
+    Ok(match style {
+        PointStyle::Tuple => format!("({x}, {y}, {z})"),
+        PointStyle::Json => format!(r#"{{"x": {x}, "y": {y}, "z": {z}}}"#),
+    })
+}
+
+/// Extracts the `Point` passed as the single argument to `method`.
+pub(crate) fn point_arg(method: &str, args: &[Value]) -> Result<Point, Error> {
+    let (value,): (&Value,) = from_args(args)?;
+    value
+        .downcast_object_ref::<Point>()
+        .copied()
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("{method}() expects a point, got {}", value.kind()),
+            )
+        })
+}
+
+impl Object for Point {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        match key.as_str()? {
+            "x" => Some(Value::from(self.0)),
+            "y" => Some(Value::from(self.1)),
+            "z" => Some(Value::from(self.2)),
+            _ => None,
+        }
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Str(&["x", "y", "z"])
+    }
+
+    // Serialization (and with it `tojson`) still goes through `enumerate`,
+    // so only the template output changes.
+    fn render(self: &Arc<Self>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+
+    // Used for `==`, `sort` and `unique`.  Hashing goes through `enumerate`,
+    // which yields the same coordinates, so equal points hash alike.
+    fn custom_cmp(self: &Arc<Self>, other: &DynObject) -> Option<Ordering> {
+        Some(self.total_cmp(other.downcast_ref::<Point>()?))
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &State<'_, '_>,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        match method {
+            "length" => {
+                from_args::<()>(args)?;
+                Ok(Value::from(self.length()))
+            }
+            "dot" => Ok(Value::from(self.dot(&point_arg(method, args)?))),
+            "cross" => Ok(Value::from_object(self.cross(&point_arg(method, args)?))),
+            "distance" => Ok(Value::from(self.distance(&point_arg(method, args)?))),
+            "scale" => {
+                let (factor,): (f32,) = from_args(args)?;
+                Ok(Value::from_object(self.scale(factor)))
+            }
+            "normalize" => {
+                from_args::<()>(args)?;
+                self.normalize().map(Value::from_object).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidOperation,
+                        "cannot normalize a point of length 0",
+                    )
+                })
+            }
+            _ => Err(Error::from(ErrorKind::UnknownMethod)),
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! The custom filters and functions of this crate, with documentation.
+//!
+//! [`ENTRIES`] is the single place where callables are registered:
+//! [`crate::add_extensions`] adds every entry to an environment, the
+//! `list-filters` command prints them and templates can call
+//! `help("slugify")` to read about one of them.
+
+use crate::{filters, functions, geometry, introspection, limits, point, svg};
+use minijinja::{Environment, Error, ErrorKind};
+use std::fmt::{self, Write};
+
+/// Whether an [`Entry`] is used as `value | name` or as `name(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Filter,
+    Function,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Filter => "filter",
+            Kind::Function => "function",
+        })
+    }
+}
+
+/// A registered filter or function.
+pub struct Entry {
+    pub name: &'static str,
+    pub kind: Kind,
+    /// How it is called, e.g. `value | repeat(n)`.
+    pub signature: &'static str,
+    /// The keyword arguments it accepts and what they do.
+    pub kwargs: &'static [(&'static str, &'static str)],
+    pub description: &'static str,
+    /// A template using it.  The tests render it as `example.txt` and check
+    /// that it produces `example_output`.
+    pub example: &'static str,
+    pub example_output: &'static str,
+    register: fn(&mut Environment<'_>),
+}
+
+impl Entry {
+    /// Adds the filter or function to `env`.
+    pub fn register(&self, env: &mut Environment<'_>) {
+        (self.register)(env)
+    }
+
+    /// The full documentation, as shown by `help(name)`.
+    pub fn help(&self) -> String {
+        let mut rv = format!(
+            "{} ({})\n\n    {}\n\n{}\n",
+            self.name, self.kind, self.signature, self.description
+        );
+        if !self.kwargs.is_empty() {
+            rv.push_str("\nKeyword arguments:\n");
+            let width = self
+                .kwargs
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or(0);
+            for (name, doc) in self.kwargs {
+                let _ = writeln!(rv, "    {name:width$}  {doc}");
+            }
+        }
+        let _ = write!(
+            rv,
+            "\nExample:\n    {}\n    => {}",
+            self.example, self.example_output
+        );
+        rv
+    }
+}
+
+/// Every filter and function [`crate::add_extensions`] registers.
+pub const ENTRIES: &[Entry] = &[
+    Entry {
+        name: "repeat",
+        kind: Kind::Filter,
+        signature: "value | repeat(n)",
+        kwargs: &[],
+        description: "Repeats a string n times.  Fails if the result would exceed the output limit.",
+        example: "{{ 'ab' | repeat(3) }}",
+        example_output: "ababab",
+        register: |env| {
+            env.add_filter("repeat", |value: &str, n: usize| {
+                limits::repeat(value, n, limits::DEFAULT_MAX_OUTPUT)
+            })
+        },
+    },
+    Entry {
+        name: "slugify",
+        kind: Kind::Filter,
+        signature: "value | slugify(separator='-', max_length=none, lowercase=true)",
+        kwargs: &[
+            ("separator", "put between words (default `-`)"),
+            (
+                "max_length",
+                "cut the slug at a word boundary to at most this many bytes",
+            ),
+            ("lowercase", "lowercase the slug (default true)"),
+        ],
+        description: "Turns text into an ASCII slug for URLs and anchors.",
+        example: "{{ 'Héllo, World!' | slugify }}",
+        example_output: "hello-world",
+        register: |env| env.add_filter("slugify", filters::slugify),
+    },
+    Entry {
+        name: "unique_slug",
+        kind: Kind::Filter,
+        signature: "value | unique_slug(separator='-', max_length=none, lowercase=true)",
+        kwargs: &[
+            ("separator", "put between words (default `-`)"),
+            (
+                "max_length",
+                "cut the slug at a word boundary to at most this many bytes",
+            ),
+            ("lowercase", "lowercase the slug (default true)"),
+        ],
+        description: "Like slugify, but hands out each slug only once per render by appending -2, -3, ... to duplicates.",
+        example: "{{ 'Intro' | unique_slug }} {{ 'Intro' | unique_slug }}",
+        example_output: "intro intro-2",
+        register: |env| env.add_filter("unique_slug", filters::unique_slug),
+    },
+    Entry {
+        name: "append_template",
+        kind: Kind::Filter,
+        signature: "value | append_template",
+        kwargs: &[],
+        description: "Appends a dash and the name of the template being rendered.",
+        example: "{{ 'page' | append_template }}",
+        example_output: "page-example.txt",
+        register: |env| env.add_filter("append_template", filters::append_template),
+    },
+    Entry {
+        name: "format_point",
+        kind: Kind::Filter,
+        signature: "point | format_point(precision=none, style='tuple')",
+        kwargs: &[
+            (
+                "precision",
+                "number of decimals (default: shortest representation)",
+            ),
+            ("style", "`tuple` or `json`"),
+        ],
+        description: "Formats a point.",
+        example: "{{ point(1, 2.5, 3) | format_point(precision=1) }}",
+        example_output: "(1.0, 2.5, 3.0)",
+        register: |env| env.add_filter("format_point", point::format_point),
+    },
+    Entry {
+        name: "to_svg_path",
+        kind: Kind::Filter,
+        signature: "shape | to_svg_path",
+        kwargs: &[],
+        description: "Turns a polygon, path or list of points into SVG path data using x and y.  Polygons are closed with Z.",
+        example: "{{ polygon([[0, 0, 0], [4, 0, 0], [0, 3, 0]]) | to_svg_path }}",
+        example_output: "M 0 0 L 4 0 L 0 3 Z",
+        register: |env| env.add_filter("to_svg_path", svg::to_svg_path),
+    },
+    Entry {
+        name: "viewbox",
+        kind: Kind::Filter,
+        signature: "shapes | viewbox(padding=0)",
+        kwargs: &[("padding", "grow the box by this much on each side")],
+        description: "Computes an SVG viewBox enclosing every point found in the value.",
+        example: "{{ [[0, 0, 0], [4, 3, 0]] | viewbox(padding=1) }}",
+        example_output: "-1 -1 6 5",
+        register: |env| env.add_filter("viewbox", svg::viewbox),
+    },
+    Entry {
+        name: "modify",
+        kind: Kind::Function,
+        signature: "modify(values, filter=none, unique_by=none, sort_by=none, reverse=false, shuffle=none, offset=0, step=1, limit=none, chunk=none)",
+        kwargs: &[
+            (
+                "filter",
+                "an expression kept items must satisfy, e.g. `price > 3`",
+            ),
+            (
+                "unique_by",
+                "keep the first item for each value of an attribute path",
+            ),
+            ("sort_by", "sort by an attribute path such as `user.name`"),
+            ("reverse", "reverse the list"),
+            ("shuffle", "shuffle with this integer seed"),
+            ("offset", "skip this many items"),
+            ("step", "take every step-th item"),
+            ("limit", "keep at most this many items"),
+            ("chunk", "split the result into lists of this many items"),
+        ],
+        description: "Transforms a list.  The keyword arguments are applied in the order they appear in the signature.",
+        example: "{{ modify([1, 2, 3, 4, 5], reverse=true, limit=3) }}",
+        example_output: "[5, 4, 3]",
+        register: |env| env.add_function("modify", functions::modify),
+    },
+    Entry {
+        name: "mathematical_fold",
+        kind: Kind::Function,
+        signature: "mathematical_fold(*numbers, op)",
+        kwargs: &[("op", "`add`, `sub`, `mul`, `div`, `min`, `max` or `avg`")],
+        description: "Folds numbers with an operation.  Integers stay integers except for div and avg.",
+        example: "{{ mathematical_fold(1, 2, 3, 4, op='mul') }}",
+        example_output: "24",
+        register: |env| env.add_function("mathematical_fold", functions::mathematical_fold),
+    },
+    Entry {
+        name: "reduce",
+        kind: Kind::Function,
+        signature: "reduce(items, fn, initial=none, numeric=none)",
+        kwargs: &[
+            ("initial", "the starting value (default: the first item)"),
+            (
+                "numeric",
+                "turn text results that look like numbers into numbers (default: whether initial is a number)",
+            ),
+        ],
+        description: "Calls fn(accumulator, item) for each item and returns the last result.  fn can be a macro; macros return text, which is turned back into numbers when initial is a number.",
+        example: "{% macro add(a, b) %}{{ a + b }}{% endmacro %}{{ reduce([1, 2, 3], add, initial=10) }}",
+        example_output: "16",
+        register: |env| env.add_function("reduce", functions::reduce),
+    },
+    Entry {
+        name: "point",
+        kind: Kind::Function,
+        signature: "point(x, y, z) or point(value)",
+        kwargs: &[],
+        description: "Creates a 3D point from three numbers, a list or a map with x, y and z.",
+        example: "{{ point([1, 2, 3]).x }}",
+        example_output: "1.0",
+        register: |env| env.add_function("point", point::point),
+    },
+    Entry {
+        name: "polygon",
+        kind: Kind::Function,
+        signature: "polygon(points)",
+        kwargs: &[],
+        description: "Creates a closed polygon with the methods area(), perimeter(), centroid() and bbox().",
+        example: "{{ polygon([[0, 0, 0], [2, 0, 0], [2, 2, 0], [0, 2, 0]]).area() }}",
+        example_output: "4.0",
+        register: |env| env.add_function("polygon", geometry::polygon),
+    },
+    Entry {
+        name: "path",
+        kind: Kind::Function,
+        signature: "path(points)",
+        kwargs: &[],
+        description: "Creates an open path with the methods length(), bbox() and closed().",
+        example: "{{ path([[0, 0, 0], [3, 4, 0]]).length() }}",
+        example_output: "5.0",
+        register: |env| env.add_function("path", geometry::path),
+    },
+    Entry {
+        name: "bbox",
+        kind: Kind::Function,
+        signature: "bbox(points)",
+        kwargs: &[],
+        description: "The axis-aligned bounding box of some points, with min, max, width, height and depth and the methods contains(point) and area().",
+        example: "{{ bbox([[0, 0, 0], [2, 3, 1]]).height }}",
+        example_output: "3.0",
+        register: |env| env.add_function("bbox", geometry::bbox),
+    },
+    Entry {
+        name: "context_info",
+        kind: Kind::Function,
+        signature: "context_info()",
+        kwargs: &[],
+        description: "Returns the current template, block, auto-escape mode, loaded templates, include stack and the type of each visible variable.",
+        example: "{% set n = 1 %}{{ context_info().variables.n }}",
+        example_output: "number",
+        register: |env| env.add_function("context_info", introspection::context_info),
+    },
+    Entry {
+        name: "debug",
+        kind: Kind::Function,
+        signature: "debug(format=none)",
+        kwargs: &[(
+            "format",
+            "`html` or `text` (default: html in auto-escaped templates)",
+        )],
+        description: "Dumps context_info() plus a preview of each variable.",
+        example: "{{ debug(format='html') is startingwith '<table' }}",
+        example_output: "true",
+        register: |env| env.add_function("debug", introspection::debug),
+    },
+    Entry {
+        name: "help",
+        kind: Kind::Function,
+        signature: "help(name=none)",
+        kwargs: &[],
+        description: "Documents a filter or function.  Without a name, lists all of them.",
+        example: "{{ 'value | repeat(n)' in help('repeat') }}",
+        example_output: "true",
+        register: |env| env.add_function("help", help),
+    },
+];
+
+/// Registers only the entries called `names` on `env`.
+pub fn register(env: &mut Environment<'_>, names: &[&str]) -> Result<(), Error> {
+    for name in names {
+        find(name)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("no filter or function named `{name}`"),
+                )
+            })?
+            .register(env);
+    }
+    Ok(())
+}
+
+/// Looks up the entry called `name`.
+pub fn find(name: &str) -> Option<&'static Entry> {
+    ENTRIES.iter().find(|entry| entry.name == name)
+}
+
+/// The entries of one kind.
+pub fn of_kind(kind: Kind) -> impl Iterator<Item = &'static Entry> {
+    ENTRIES.iter().filter(move |entry| entry.kind == kind)
+}
+
+/// The signature and description of every entry of the given kinds.
+pub fn listing(kinds: &[Kind]) -> String {
+    let mut rv = String::new();
+    for &kind in kinds {
+        if !rv.is_empty() {
+            rv.push('\n');
+        }
+        let _ = writeln!(rv, "{kind}s:");
+        for entry in of_kind(kind) {
+            let _ = writeln!(rv, "  {}\n      {}", entry.signature, entry.description);
+        }
+    }
+    rv
+}
+
+/// The `help(name)` global function.
+///
+/// Returns the documentation of `name`, or a listing of all filters and
+/// functions if `name` is omitted.
+pub fn help(name: Option<&str>) -> Result<String, Error> {
+    let Some(name) = name else {
+        return Ok(listing(&[Kind::Filter, Kind::Function]));
+    };
+    find(name).map(Entry::help).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("help: no filter or function named `{name}`"),
+        )
+    })
+}
@@ -0,0 +1,399 @@
+//! A rendering profile for templates that can't be trusted.
+//!
+//! [`sandboxed_environment`] combines everything that keeps a hostile
+//! template in check: the fuel and output [`Limits`], a low recursion limit
+//! for macros and includes, a wall-clock timeout, a memory limit and an
+//! allowlist of the functions and extension filters a template may call.
+//!
+//! Templates are compiled and rendered in a forked child process.  minijinja
+//! builds strings such as `'x' * 10000000000` or `s ~ s` without asking, and
+//! a failed allocation aborts the process instead of returning an error, so
+//! only a separate process can survive it.  The child's address space is
+//! capped on Linux, and a child that runs past the timeout is killed.
+//!
+//! minijinja's builtin filters and tests stay available; they can't do more
+//! than the limits allow.
+
+use crate::limits::Limits;
+use crate::registry::{self, Kind};
+use minijinja::value::Value;
+use minijinja::{Environment, Error, ErrorKind};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The functions and extension filters a sandbox allows by default.
+///
+/// `modify`, `mathematical_fold`, `context_info` and `debug` are left out, as
+/// are minijinja's own `debug` and `namespace`.
+pub const DEFAULT_ALLOWED: &[&str] = &[
+    // filters
+    "repeat",
+    "slugify",
+    "unique_slug",
+    "format_point",
+    // functions
+    "range",
+    "dict",
+    "point",
+    "polygon",
+    "path",
+    "bbox",
+    "help",
+];
+
+/// The memory in bytes a render may allocate by default.
+pub const DEFAULT_MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// What a [`Sandbox`] allows.
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Must include a fuel limit, see [`Sandbox::new`].
+    pub limits: Limits,
+    /// How deeply macros, includes and blocks may nest.
+    pub recursion_limit: usize,
+    /// How long a render may take before it is killed.
+    pub timeout: Duration,
+    /// How many bytes of address space a render may map on top of what the
+    /// process already uses.  Only enforced on Linux.
+    pub max_memory: usize,
+    /// The functions and extension filters templates may call.
+    pub allowed: Vec<String>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        SandboxConfig {
+            limits: Limits::default(),
+            recursion_limit: 50,
+            timeout: Duration::from_secs(2),
+            max_memory: DEFAULT_MAX_MEMORY,
+            allowed: DEFAULT_ALLOWED
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// An environment that renders with the limits of a [`SandboxConfig`].
+pub struct Sandbox {
+    env: Environment<'static>,
+    config: SandboxConfig,
+}
+
+/// A sandbox with the default [`SandboxConfig`].
+pub fn sandboxed_environment() -> Sandbox {
+    Sandbox::new(SandboxConfig::default()).expect("the default sandbox config is valid")
+}
+
+impl Sandbox {
+    /// Creates a sandbox.
+    ///
+    /// Fails if `config.limits.fuel` is `None`: without fuel every endless
+    /// loop would hold a process for the whole timeout.
+    pub fn new(config: SandboxConfig) -> Result<Sandbox, Error> {
+        if config.limits.fuel.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                "a sandbox needs a fuel limit so that endless loops stop",
+            ));
+        }
+        let mut env = Environment::new();
+        crate::add_extensions(&mut env);
+        config.limits.apply(&mut env);
+        env.set_recursion_limit(config.recursion_limit);
+
+        let allowed = |name: &str| config.allowed.iter().any(|allowed| allowed == name);
+        for entry in registry::of_kind(Kind::Filter) {
+            if !allowed(entry.name) {
+                env.remove_filter(entry.name);
+            }
+        }
+        let functions: Vec<String> = env.globals().map(|(name, _)| name.to_string()).collect();
+        for name in functions {
+            if !allowed(&name) {
+                env.remove_global(&name);
+            }
+        }
+
+        Ok(Sandbox { env, config })
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Adds a template.  Syntax errors are reported right away.
+    ///
+    /// The template is compiled in a child process first, since compiling
+    /// already folds constant expressions like `'x' * 10000000000`.
+    pub fn add_template(&mut self, name: &str, source: &str) -> Result<(), Error> {
+        self.run(|env, _| {
+            env.template_from_named_str(name, source)?;
+            Ok(String::new())
+        })?;
+        self.env
+            .add_template_owned(name.to_string(), source.to_string())
+    }
+
+    /// Renders the template `name` within the sandbox's limits.
+    ///
+    /// Side effects of the render, such as values stored by functions in
+    /// `ctx`, stay in the child process.
+    pub fn render(&self, name: &str, ctx: Value) -> Result<String, Error> {
+        self.run(|env, limits| limits.render(&env.get_template(name)?, ctx))
+    }
+
+    /// Compiles and renders `source` like [`render`](Self::render).
+    pub fn render_str(&self, source: &str, ctx: Value) -> Result<String, Error> {
+        self.run(|env, limits| limits.render(&env.template_from_str(source)?, ctx))
+    }
+
+    /// Runs `f` in a child process and returns what it returned there.
+    fn run<F>(&self, f: F) -> Result<String, Error>
+    where
+        F: FnOnce(&Environment<'static>, &Limits) -> Result<String, Error>,
+    {
+        let (mut reader, pid) = {
+            // No other thread may fork while the write end is open here, or
+            // its child would keep the pipe from ever reaching its end.
+            static FORK: Mutex<()> = Mutex::new(());
+            let _guard = FORK.lock().unwrap_or_else(|err| err.into_inner());
+            let (reader, mut writer) = io::pipe().map_err(|err| os_error("create a pipe", err))?;
+            // SAFETY: the child only renders, writes to the pipe and exits
+            // without running destructors or `atexit` handlers.
+            match unsafe { libc::fork() } {
+                -1 => return Err(os_error("fork", io::Error::last_os_error())),
+                0 => {
+                    limit_memory(self.config.max_memory);
+                    let rv =
+                        panic::catch_unwind(AssertUnwindSafe(|| f(&self.env, &self.config.limits)))
+                            .unwrap_or_else(|_| {
+                                Err(Error::new(
+                                    ErrorKind::InvalidOperation,
+                                    "rendering panicked",
+                                ))
+                            });
+                    let _ = serde_json::to_writer(&mut writer, &Outcome::from_result(rv));
+                    let _ = writer.flush();
+                    // SAFETY: see above.
+                    unsafe { libc::_exit(0) }
+                }
+                pid => (reader, pid),
+            }
+        };
+
+        let output = read_until(&mut reader, Instant::now() + self.config.timeout);
+        if output.is_err() {
+            // SAFETY: `pid` is our child and has not been waited for yet.
+            unsafe { libc::kill(pid, libc::SIGKILL) };
+        }
+        let mut status = 0;
+        // SAFETY: as above.
+        unsafe { libc::waitpid(pid, &mut status, 0) };
+        let output = output.map_err(|err| match err.kind() {
+            io::ErrorKind::TimedOut => Error::new(
+                ErrorKind::InvalidOperation,
+                format!("rendering took longer than {:?}", self.config.timeout),
+            ),
+            _ => os_error("read the output", err),
+        })?;
+
+        if libc::WIFSIGNALED(status) {
+            let detail = match libc::WTERMSIG(status) {
+                libc::SIGABRT => format!(
+                    "rendering was aborted, most likely because it needed more than {} bytes of memory",
+                    self.config.max_memory
+                ),
+                signal => format!("rendering was killed by signal {signal}"),
+            };
+            return Err(Error::new(ErrorKind::InvalidOperation, detail));
+        }
+        let outcome: Outcome = serde_json::from_slice(&output).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                "rendering ended without a result",
+            )
+            .with_source(err)
+        })?;
+        outcome.into_result()
+    }
+}
+
+fn os_error(what: &str, err: io::Error) -> Error {
+    Error::new(
+        ErrorKind::InvalidOperation,
+        format!("could not {what} for rendering"),
+    )
+    .with_source(err)
+}
+
+/// Reads `reader` to its end, failing with [`io::ErrorKind::TimedOut`] once
+/// `deadline` has passed.
+fn read_until(reader: &mut io::PipeReader, deadline: Instant) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buf = [0; 64 * 1024];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let mut fd = libc::pollfd {
+            fd: reader.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = libc::c_int::try_from(left.as_millis() + 1).unwrap_or(libc::c_int::MAX);
+        // SAFETY: `fd` is a single valid `pollfd`.
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            0 => continue,
+            -1 => match io::Error::last_os_error() {
+                err if err.kind() == io::ErrorKind::Interrupted => continue,
+                err => return Err(err),
+            },
+            _ => {}
+        }
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(output),
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Caps the address space of this process at `max_memory` bytes more than
+/// it uses now.  Does nothing where `/proc/self/statm` doesn't exist.
+fn limit_memory(max_memory: usize) {
+    // The first field is the size of the address space in pages.
+    let Some(pages) = fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|statm| statm.split_whitespace().next()?.parse::<usize>().ok())
+    else {
+        return;
+    };
+    // SAFETY: `sysconf` has no preconditions.
+    let page_size = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(4096);
+    let limit = pages.saturating_mul(page_size).saturating_add(max_memory) as libc::rlim_t;
+    let limit = libc::rlimit {
+        rlim_cur: limit,
+        rlim_max: limit,
+    };
+    // SAFETY: `limit` is a valid `rlimit`.
+    unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) };
+}
+
+/// The result of a render as the child sends it to the parent.
+#[derive(Serialize, Deserialize)]
+enum Outcome {
+    Rendered(String),
+    /// The error followed by its sources.
+    Failed(Vec<Failure>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Failure {
+    /// The `Debug` name of the [`ErrorKind`], or `None` for an error that
+    /// doesn't come from minijinja.
+    kind: Option<String>,
+    detail: Option<String>,
+    /// The template and line a minijinja error points at.
+    location: Option<(String, usize)>,
+}
+
+/// The error kinds a [`Failure`] can name.
+const KINDS: &[ErrorKind] = &[
+    ErrorKind::NonPrimitive,
+    ErrorKind::NonKey,
+    ErrorKind::InvalidOperation,
+    ErrorKind::SyntaxError,
+    ErrorKind::TemplateNotFound,
+    ErrorKind::TooManyArguments,
+    ErrorKind::MissingArgument,
+    ErrorKind::UnknownFilter,
+    ErrorKind::UnknownTest,
+    ErrorKind::UnknownFunction,
+    ErrorKind::UnknownMethod,
+    ErrorKind::BadEscape,
+    ErrorKind::UndefinedError,
+    ErrorKind::BadSerialization,
+    ErrorKind::CannotDeserialize,
+    ErrorKind::BadInclude,
+    ErrorKind::EvalBlock,
+    ErrorKind::CannotUnpack,
+    ErrorKind::WriteFailure,
+    ErrorKind::OutOfFuel,
+    ErrorKind::UnknownBlock,
+];
+
+impl Outcome {
+    fn from_result(rv: Result<String, Error>) -> Outcome {
+        let err = match rv {
+            Ok(output) => return Outcome::Rendered(output),
+            Err(err) => err,
+        };
+        let chain = std::iter::successors(Some(&err as &dyn std::error::Error), |err| err.source());
+        let failures = chain.map(|err| match err.downcast_ref::<Error>() {
+            Some(err) => Failure {
+                kind: Some(format!("{:?}", err.kind())),
+                detail: err.detail().map(str::to_string),
+                location: err
+                    .name()
+                    .map(|name| (name.to_string(), err.line().unwrap_or(0))),
+            },
+            None => Failure {
+                kind: None,
+                detail: Some(err.to_string()),
+                location: None,
+            },
+        });
+        Outcome::Failed(failures.collect())
+    }
+
+    /// Rebuilds the error and its sources.  minijinja doesn't let errors be
+    /// given a location, so it becomes part of the detail, which displays
+    /// the same.
+    fn into_result(self) -> Result<String, Error> {
+        let failures = match self {
+            Outcome::Rendered(output) => return Ok(output),
+            Outcome::Failed(failures) => failures,
+        };
+        let mut foreign = None;
+        let mut rv: Option<Error> = None;
+        for failure in failures.into_iter().rev() {
+            let detail = failure.detail.unwrap_or_default();
+            let Some(kind) = failure.kind else {
+                // Anything below a foreign error is dropped with it.
+                foreign = Some(io::Error::other(detail));
+                rv = None;
+                continue;
+            };
+            let kind = KINDS
+                .iter()
+                .copied()
+                .find(|known| format!("{known:?}") == kind)
+                .unwrap_or(ErrorKind::InvalidOperation);
+            let detail = match failure.location {
+                Some((name, line)) if detail.is_empty() => format!("(in {name}:{line})"),
+                Some((name, line)) => format!("{detail} (in {name}:{line})"),
+                None => detail,
+            };
+            let err = if detail.is_empty() {
+                Error::from(kind)
+            } else {
+                Error::new(kind, detail)
+            };
+            rv = Some(match (rv.take(), foreign.take()) {
+                (Some(source), _) => err.with_source(source),
+                (None, Some(source)) => err.with_source(source),
+                (None, None) => err,
+            });
+        }
+        Err(rv.unwrap_or_else(|| Error::new(ErrorKind::InvalidOperation, "rendering failed")))
+    }
+}
@@ -0,0 +1,129 @@
+//! SVG output for points and geometry objects.
+//!
+//! The templates live in `templates/svg/` and are compiled into the binary,
+//! see [`crate::embedded`].
+//! Templates ending in `.svg` are auto-escaped like HTML, which also makes
+//! text and attribute values safe in XML.
+
+use crate::embedded::EmbeddedLoader;
+use crate::geometry::{BoundingBox, Path, Polygon};
+use crate::kwargs::from_kwargs;
+use crate::point::Point;
+use minijinja::value::{Kwargs, Value, ValueKind};
+use minijinja::{AutoEscape, Environment, Error, ErrorKind, default_auto_escape_callback};
+use serde::Deserialize;
+
+/// Picks HTML escaping for `.svg` templates on top of minijinja's defaults.
+pub fn auto_escape(name: &str) -> AutoEscape {
+    match name.strip_suffix(".j2").unwrap_or(name).rsplit('.').next() {
+        Some("svg") => AutoEscape::Html,
+        _ => default_auto_escape_callback(name),
+    }
+}
+
+/// An environment with the crate's extensions, which include the SVG
+/// filters, and the bundled SVG templates.
+///
+/// Templates are read from the directory in
+/// [`DISK_ENV`](crate::embedded::DISK_ENV) first if it is set.
+pub fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    crate::add_extensions(&mut env);
+    env.set_auto_escape_callback(auto_escape);
+    EmbeddedLoader::from_env().install(&mut env);
+    env
+}
+
+/// Turns a polygon, path or sequence of points into SVG path data.
+///
+/// Polygons are closed with `Z`; everything else is left open.  Only the
+/// `x` and `y` coordinates are used.
+pub fn to_svg_path(value: &Value) -> Result<String, Error> {
+    let (points, closed) = if let Some(polygon) = value.downcast_object_ref::<Polygon>() {
+        (polygon.0.clone(), true)
+    } else if let Some(path) = value.downcast_object_ref::<Path>() {
+        (path.0.clone(), false)
+    } else {
+        let points = value
+            .try_iter()
+            .ok()
+            .and_then(|iter| iter.map(|item| Point::from_value(&item)).collect())
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!(
+                        "to_svg_path expects a polygon, path or list of points, got {}",
+                        value.kind()
+                    ),
+                )
+            })?;
+        (points, false)
+    };
+
+    let mut rv = String::new();
+    for (idx, p) in points.iter().enumerate() {
+        if idx > 0 {
+            rv.push(' ');
+        }
+        rv.push(if idx == 0 { 'M' } else { 'L' });
+        rv.push_str(&format!(" {} {}", p.0, p.1));
+    }
+    if closed && !points.is_empty() {
+        rv.push_str(" Z");
+    }
+    Ok(rv)
+}
+
+/// Collects every point found in `value`, looking into sequences, shapes,
+/// bounding boxes and maps with a `points` key or `x`/`y` coordinates.
+fn collect_points(value: &Value, out: &mut Vec<Point>) {
+    if let Some(point) = Point::from_value(value) {
+        out.push(point);
+    } else if let Some(bbox) = value.downcast_object_ref::<BoundingBox>() {
+        out.extend([bbox.min, bbox.max]);
+    } else if value.kind() == ValueKind::Map {
+        if let Ok(points) = value.get_attr("points")
+            && !points.is_undefined()
+        {
+            collect_points(&points, out);
+            return;
+        }
+        let get = |key: &str| f32::try_from(value.get_attr(key).ok()?).ok();
+        if let (Some(x), Some(y)) = (get("x"), get("y")) {
+            out.push(Point(x, y, get("z").unwrap_or(0.0)));
+        }
+    } else if let Ok(iter) = value.try_iter() {
+        for item in iter {
+            collect_points(&item, out);
+        }
+    }
+}
+
+/// The keyword arguments of [`viewbox`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ViewboxOptions {
+    padding: f32,
+}
+
+/// Computes an SVG `viewBox` (`min-x min-y width height`) enclosing all
+/// points in `value`, grown by `padding` on each side.
+pub fn viewbox(value: &Value, kwargs: Kwargs) -> Result<String, Error> {
+    let ViewboxOptions { padding } = from_kwargs("viewbox", &kwargs)?;
+
+    let mut points = Vec::new();
+    collect_points(value, &mut points);
+    let bbox = BoundingBox::from_points(&points).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            "viewbox needs at least one point",
+        )
+    })?;
+    Ok(format!(
+        "{} {} {} {}",
+        bbox.min.0 - padding,
+        bbox.min.1 - padding,
+        bbox.width() + 2.0 * padding,
+        bbox.height() + 2.0 * padding,
+    ))
+}
@@ -0,0 +1,176 @@
+//! A loader that layers several template directories on top of each other.
+//!
+//! Layers are searched in the order they were added, so a white-label
+//! product can put its overrides in front of a theme and the theme in front
+//! of the built-in defaults:
+//!
+//! ```no_run
+//! use minijinja::Environment;
+//! use minijinja_exploration::theme::ThemeLoader;
+//!
+//! # fn main() -> Result<(), minijinja::Error> {
+//! let loader = ThemeLoader::new()
+//!     .with_layer("project", "project/templates")?
+//!     .with_layer("theme", "themes/acme")?
+//!     .with_layer("defaults", "templates")?;
+//! let mut env = Environment::new();
+//! loader.install(&mut env);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A name prefixed with a layer, such as `theme:base.html`, starts the
+//! search at that layer.  This lets an override extend the template it
+//! replaces with `{% extends "theme:base.html" %}`.
+
+use minijinja::{Environment, Error, ErrorKind};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Where a template was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub layer: String,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.layer, self.path.display())
+    }
+}
+
+/// Loads templates from an ordered list of directories.
+///
+/// Clones share the record of which file each template was loaded from.
+#[derive(Debug, Clone, Default)]
+pub struct ThemeLoader {
+    layers: Vec<(String, PathBuf)>,
+    origins: Arc<Mutex<BTreeMap<String, Origin>>>,
+}
+
+impl ThemeLoader {
+    pub fn new() -> ThemeLoader {
+        ThemeLoader::default()
+    }
+
+    /// Adds a layer below the ones added before.
+    ///
+    /// Fails if `name` is empty, contains a `:` or is already taken, since
+    /// `name:` prefixes could not reach the layer then.
+    pub fn with_layer(
+        mut self,
+        name: impl Into<String>,
+        dir: impl Into<PathBuf>,
+    ) -> Result<ThemeLoader, Error> {
+        let name = name.into();
+        let problem = if name.is_empty() || name.contains(':') {
+            "is not a valid layer name"
+        } else if self.layers.iter().any(|(taken, _)| *taken == name) {
+            "is used for more than one layer"
+        } else {
+            self.layers.push((name, dir.into()));
+            return Ok(self);
+        };
+        Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("`{name}` {problem}"),
+        ))
+    }
+
+    /// The layers from highest to lowest precedence.
+    pub fn layers(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.layers
+            .iter()
+            .map(|(name, dir)| (name.as_str(), dir.as_path()))
+    }
+
+    /// Finds the file `name` would be loaded from without loading it.
+    pub fn resolve(&self, name: &str) -> Result<Option<Origin>, Error> {
+        Ok(self.find(name)?.map(|(origin, _)| origin))
+    }
+
+    /// Loads `name` and remembers where it came from, see [`origin`](Self::origin).
+    pub fn load(&self, name: &str) -> Result<Option<String>, Error> {
+        let Some((origin, source)) = self.find(name)? else {
+            return Ok(None);
+        };
+        self.origins
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), origin);
+        Ok(Some(source))
+    }
+
+    /// Where the template `name` was loaded from, if it was loaded.
+    pub fn origin(&self, name: &str) -> Option<Origin> {
+        self.origins.lock().unwrap().get(name).cloned()
+    }
+
+    /// Every template loaded so far with its origin, sorted by name.
+    pub fn origins(&self) -> Vec<(String, Origin)> {
+        let origins = self.origins.lock().unwrap();
+        origins
+            .iter()
+            .map(|(name, origin)| (name.clone(), origin.clone()))
+            .collect()
+    }
+
+    /// Makes this the loader of `env`.
+    pub fn install(&self, env: &mut Environment<'_>) {
+        let loader = self.clone();
+        env.set_loader(move |name| loader.load(name));
+    }
+
+    fn find(&self, name: &str) -> Result<Option<(Origin, String)>, Error> {
+        let (layers, name) = match name.split_once(':') {
+            Some((layer, rest)) => {
+                let Some(start) = self.layers.iter().position(|(l, _)| l == layer) else {
+                    return Err(Error::new(
+                        ErrorKind::TemplateNotFound,
+                        format!("there is no template layer named `{layer}`"),
+                    ));
+                };
+                (&self.layers[start..], rest)
+            }
+            None => (&self.layers[..], name),
+        };
+        for (layer, dir) in layers {
+            let Some(path) = safe_join(dir, name) else {
+                return Ok(None);
+            };
+            match fs::read_to_string(&path) {
+                Ok(source) => {
+                    let layer = layer.clone();
+                    return Ok(Some((Origin { layer, path }, source)));
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidOperation,
+                        format!("could not read template from {}", path.display()),
+                    )
+                    .with_source(err));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Joins `name` onto `dir`, refusing hidden and parent segments like
+/// minijinja's `path_loader` does.
+fn safe_join(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut rv = dir.to_path_buf();
+    for segment in name.split('/') {
+        if segment.starts_with('.') || segment.contains('\\') {
+            return None;
+        }
+        rv.push(segment);
+    }
+    Some(rv)
+}
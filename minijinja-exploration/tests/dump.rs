@@ -0,0 +1,39 @@
+use minijinja_exploration::commands::dump::compile;
+
+fn ops(json: &serde_json::Value) -> Vec<&str> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["instruction"]["op"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn dumps_instructions_of_the_mod_vec_example() {
+    let compiled = compile("mod_vec", "{{ modify(input_vec, limit=4, reverse=true) }}").unwrap();
+    let json = compiled.to_json(Some("instructions"));
+    assert!(json.get("ast").is_none());
+    assert_eq!(
+        ops(&json["instructions"]),
+        ["Lookup", "LoadConst", "CallFunction", "Emit"]
+    );
+    assert_eq!(json["instructions"][1]["instruction"]["arg"]["limit"], 4);
+    assert_eq!(json["instructions"][0]["line"], 1);
+}
+
+#[test]
+fn dumps_blocks_separately() {
+    let compiled = compile("t", "a{% block body %}{{ x }}{% endblock %}").unwrap();
+    let json = compiled.to_json(None);
+    assert!(json["ast"].is_object());
+    assert_eq!(ops(&json["blocks"]["body"]), ["Lookup", "Emit"]);
+
+    let listing = compiled.to_listing(Some("instructions"));
+    assert!(listing.starts_with("instructions:\n  00000 | EmitRaw(\"a\")  [line 1]\n"));
+    assert!(listing.contains("block \"body\":\n  00000 | Lookup(\"x\")"));
+}
+
+#[test]
+fn syntax_errors_are_reported() {
+    assert!(compile("t", "{{ oops").is_err());
+}
@@ -0,0 +1,57 @@
+use minijinja::path_loader;
+use minijinja_exploration::embedded::{self, EmbeddedLoader};
+use std::fs;
+
+const TEMPLATES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/templates");
+
+#[test]
+fn bundles_every_template() {
+    let names: Vec<_> = embedded::names().collect();
+    assert!(names.contains(&"svg/document.svg"), "{names:?}");
+    assert!(names.is_sorted());
+    for name in names {
+        assert_eq!(
+            embedded::get(name).unwrap().unwrap(),
+            fs::read_to_string(format!("{TEMPLATES}/{name}")).unwrap(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn behaves_like_the_path_loader() {
+    let on_disk = path_loader(TEMPLATES);
+    let loader = EmbeddedLoader::new();
+    for name in [
+        "svg/document.svg",
+        "svg//shapes.svg",
+        "/examples/greeting.txt",
+        "missing.txt",
+        "svg/../svg/document.svg",
+        "./svg/document.svg",
+        ".hidden",
+        "svg\\document.svg",
+    ] {
+        assert_eq!(loader.load(name).unwrap(), on_disk(name).unwrap(), "{name}");
+    }
+}
+
+#[test]
+fn prefers_files_on_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("examples")).unwrap();
+    fs::write(dir.path().join("examples/greeting.txt"), "Hi {{ name }}").unwrap();
+
+    let loader = EmbeddedLoader::prefer_disk(dir.path());
+    assert_eq!(loader.disk_dir(), Some(dir.path()));
+    assert_eq!(
+        loader.load("examples/greeting.txt").unwrap().as_deref(),
+        Some("Hi {{ name }}")
+    );
+    // Templates missing on disk still come from the bundle.
+    assert_eq!(
+        loader.load("svg/shapes.svg").unwrap(),
+        embedded::get("svg/shapes.svg").unwrap()
+    );
+    assert_eq!(loader.load("missing.txt").unwrap(), None);
+}
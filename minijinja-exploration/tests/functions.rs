@@ -0,0 +1,315 @@
+use minijinja::value::Value;
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::add_extensions;
+
+fn eval(expr: &str) -> Result<Value, minijinja::Error> {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    env.compile_expression(expr)?.eval(())
+}
+
+fn fold(args: &str) -> Result<Value, minijinja::Error> {
+    eval(&format!("mathematical_fold({args})"))
+}
+
+#[test]
+fn fold_integers() {
+    assert_eq!(fold("1, 2, 3, 4, op='add'").unwrap(), Value::from(10));
+    assert_eq!(fold("1, 2, 3, 4, op='mul'").unwrap(), Value::from(24));
+    assert_eq!(fold("10, 2, 3, op='sub'").unwrap(), Value::from(5));
+    assert_eq!(fold("4, -2, 9, op='min'").unwrap(), Value::from(-2));
+    assert_eq!(fold("4, -2, 9, op='max'").unwrap(), Value::from(9));
+    assert_eq!(fold("op='add'").unwrap(), Value::from(0));
+    assert_eq!(fold("op='mul'").unwrap(), Value::from(1));
+    // Integers stay integers.
+    assert_eq!(fold("2, 3, op='mul'").unwrap().to_string(), "6");
+}
+
+#[test]
+fn fold_floats_and_mixed_numbers() {
+    assert_eq!(fold("1, 2.5, op='add'").unwrap(), Value::from(3.5));
+    assert_eq!(fold("1.5, 2, op='mul'").unwrap().to_string(), "3.0");
+    assert_eq!(fold("7, 2, op='div'").unwrap(), Value::from(3.5));
+    assert_eq!(
+        fold("1, 2, 4, op='avg'").unwrap().to_string(),
+        "2.3333333333333335"
+    );
+    assert_eq!(fold("2, 0.5, 3, op='min'").unwrap(), Value::from(0.5));
+}
+
+#[test]
+fn fold_errors() {
+    let err = fold("1, 'a', op='add'").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("argument 2 is a string, expected a number"),
+        "{err}"
+    );
+
+    let err = fold("9223372036854775807, 9223372036854775807, 9223372036854775807, op='mul'")
+        .unwrap_err();
+    assert!(err.to_string().contains("integer overflow in mul"), "{err}");
+
+    let err = fold("1, 2, op='pow'").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("invalid value for `op`: unknown variant `pow`"),
+        "{err}"
+    );
+
+    assert!(
+        fold("1, 0, op='div'")
+            .unwrap_err()
+            .to_string()
+            .contains("division by zero")
+    );
+    assert_eq!(
+        fold("op='avg'").unwrap_err().kind(),
+        ErrorKind::MissingArgument
+    );
+    assert_eq!(fold("1, 2").unwrap_err().kind(), ErrorKind::MissingArgument);
+    assert_eq!(
+        fold("1, op='add', by=2").unwrap_err().kind(),
+        ErrorKind::TooManyArguments
+    );
+}
+
+fn render(source: &str) -> Result<String, minijinja::Error> {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    env.render_str(source, ())
+}
+
+#[test]
+fn reduce_with_macros() {
+    let sum = "{% macro add(a, b) %} {{ a + b }} {% endmacro %}";
+    assert_eq!(
+        render(&format!(
+            "{sum}{{{{ reduce([1, 2, 3], add, numeric=true) }}}}"
+        ))
+        .unwrap(),
+        "6"
+    );
+    // Or the macro converts its accumulator itself.
+    assert_eq!(
+        render(
+            "{% macro add(a, b) %}{{ a | int + b }}{% endmacro %}\
+             {{ reduce([1, 2, 3], add) }}"
+        )
+        .unwrap(),
+        "6"
+    );
+    // With `numeric=true` numeric macro output turns back into numbers.
+    assert_eq!(
+        render(&format!(
+            "{sum}{{{{ reduce([1, 2.5], add, initial=10, numeric=true) + 1 }}}}"
+        ))
+        .unwrap(),
+        "14.5"
+    );
+    let cat = "{% macro cat(a, b) %}{{ a }}{{ b }}{% endmacro %}";
+    assert_eq!(
+        render(&format!("{cat}{{{{ reduce(['a', 'b', 'c'], cat) }}}}")).unwrap(),
+        "abc"
+    );
+    // Otherwise text that looks like a number stays text.
+    assert_eq!(
+        render(&format!("{cat}{{{{ reduce(['0', '0', '7'], cat) }}}}")).unwrap(),
+        "007"
+    );
+    assert_eq!(
+        render(&format!("{cat}{{{{ reduce(['1', 'e', '5'], cat) }}}}")).unwrap(),
+        "1e5"
+    );
+    // A string `initial` keeps text results as text, as does `numeric=false`.
+    assert_eq!(
+        render(&format!(
+            "{cat}{{{{ reduce(['0', '7'], cat, initial='0') }}}}"
+        ))
+        .unwrap(),
+        "007"
+    );
+    assert_eq!(
+        render(&format!(
+            "{cat}{{{{ reduce(['0', '7'], cat, initial=0, numeric=false) }}}}"
+        ))
+        .unwrap(),
+        "007"
+    );
+    // A single item without `initial` is returned as is.
+    assert_eq!(
+        render(&format!("{sum}{{{{ reduce([7], add) }}}}")).unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn reduce_sums_with_a_macro_and_a_numeric_initial() {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    let orders = vec![
+        context! { price => 3 },
+        context! { price => 4.5 },
+        context! { price => 2 },
+    ];
+    assert_eq!(
+        env.render_str(
+            "{% macro sum_price(total, order) %}{{ total + order.price }}{% endmacro %}\
+             {{ reduce(orders, sum_price, initial=0) }} {{ reduce(orders, sum_price, initial=0) + 1 }}",
+            context! { orders },
+        )
+        .unwrap(),
+        "9.5 10.5"
+    );
+}
+
+#[test]
+fn reduce_with_callables() {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    env.add_function(
+        "longest",
+        |a: String, b: String| {
+            if b.len() > a.len() { b } else { a }
+        },
+    );
+    assert_eq!(
+        env.render_str("{{ reduce(['ab', 'abcd', 'abc'], longest) }}", ())
+            .unwrap(),
+        "abcd"
+    );
+}
+
+#[test]
+fn reduce_errors() {
+    let err = eval("reduce([], 1)").unwrap_err();
+    assert!(err.to_string().contains("needs an initial value"), "{err}");
+    assert_eq!(eval("reduce([], 1, initial=5)").unwrap(), Value::from(5));
+
+    let err = eval("reduce([1, 2], 1)").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(err.to_string().contains("not callable"), "{err}");
+
+    assert_eq!(
+        eval("reduce(1, 1)").unwrap_err().kind(),
+        ErrorKind::InvalidOperation
+    );
+    assert_eq!(
+        eval("reduce([1], 1, start=0)").unwrap_err().kind(),
+        ErrorKind::TooManyArguments
+    );
+}
+
+fn modify(args: &str) -> String {
+    match eval(&format!("modify({args})")) {
+        Ok(value) => value.to_string(),
+        Err(err) => panic!("modify({args}) failed: {err}"),
+    }
+}
+
+fn modify_err(args: &str) -> minijinja::Error {
+    eval(&format!("modify({args})")).expect_err(args)
+}
+
+#[test]
+fn modify_slices() {
+    let items = "[1, 2, 3, 4, 5, 6, 7]";
+    assert_eq!(
+        modify(&format!("{items}, reverse=true, limit=3")),
+        "[7, 6, 5]"
+    );
+    assert_eq!(modify(&format!("{items}, offset=2")), "[3, 4, 5, 6, 7]");
+    assert_eq!(modify(&format!("{items}, step=3")), "[1, 4, 7]");
+    assert_eq!(
+        modify(&format!("{items}, offset=1, step=2, limit=2")),
+        "[2, 4]"
+    );
+    assert_eq!(
+        modify(&format!("{items}, chunk=3")),
+        "[[1, 2, 3], [4, 5, 6], [7]]"
+    );
+    assert_eq!(modify("[], chunk=2"), "[]");
+}
+
+#[test]
+fn modify_by_attribute() {
+    let people = "[{'name': 'b', 'team': {'id': 2}}, {'name': 'a', 'team': {'id': 1}}, \
+                  {'name': 'c', 'team': {'id': 2}}]";
+    let names = |options: &str| {
+        eval(&format!(
+            "modify({people}, {options}) | map(attribute='name') | join"
+        ))
+        .unwrap()
+        .to_string()
+    };
+    assert_eq!(names("sort_by='name'"), "abc");
+    assert_eq!(names("sort_by='team.id', reverse=true"), "cba");
+    assert_eq!(names("unique_by='team.id'"), "ba");
+    assert_eq!(
+        modify("[[1, 'x'], [0, 'y']], sort_by='0'"),
+        "[[0, \"y\"], [1, \"x\"]]"
+    );
+}
+
+#[test]
+fn modify_filter() {
+    assert_eq!(modify("[1, 2, 3, 4], filter='item is even'"), "[2, 4]");
+    assert_eq!(
+        modify("[{'n': 1}, {'n': 5}, {'n': 9}], filter='n > 2 and n < item.n + 1', limit=1"),
+        "[{\"n\": 5}]"
+    );
+}
+
+#[test]
+fn modify_shuffle_is_deterministic() {
+    let items = "range(20) | list";
+    let once = modify(&format!("{items}, shuffle=42"));
+    assert_eq!(modify(&format!("{items}, shuffle=42")), once);
+    assert_ne!(modify(&format!("{items}, shuffle=7")), once);
+    assert_ne!(once, modify(items));
+    assert_eq!(
+        eval(&format!("modify({items}, shuffle=42) | sort")).unwrap(),
+        eval(items).unwrap()
+    );
+}
+
+#[test]
+fn modify_errors_name_the_kwarg() {
+    let err = modify_err("[1], limit='x'");
+    assert!(
+        err.to_string().contains("invalid value for `limit`"),
+        "{err}"
+    );
+    let err = modify_err("[1], step=0");
+    assert!(
+        err.to_string().contains("`step`: must be at least 1"),
+        "{err}"
+    );
+    let err = modify_err("[1], chunk=0");
+    assert!(
+        err.to_string().contains("`chunk`: must be at least 1"),
+        "{err}"
+    );
+    let err = modify_err("[1], filter='item >'");
+    assert!(
+        err.to_string().contains("invalid value for `filter`"),
+        "{err}"
+    );
+    let err = modify_err("[1], filter='missing.attr'");
+    assert!(
+        err.to_string()
+            .contains("invalid value for `filter`: undefined value"),
+        "{err}"
+    );
+    let err = modify_err("[1], shuffle=-1");
+    assert!(
+        err.to_string().contains("invalid value for `shuffle`"),
+        "{err}"
+    );
+
+    let err = modify_err("[1], revers=true");
+    assert_eq!(err.kind(), ErrorKind::TooManyArguments);
+    assert!(err.to_string().contains("unknown field `revers`"), "{err}");
+}
@@ -0,0 +1,129 @@
+use minijinja::value::Value;
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::add_extensions;
+use minijinja_exploration::geometry::{BoundingBox, Path, Polygon};
+use minijinja_exploration::point::Point;
+
+fn env() -> Environment<'static> {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    env
+}
+
+fn render(source: &str) -> Result<String, minijinja::Error> {
+    env().render_str(
+        source,
+        context! {
+            triangle => Value::from_object(Polygon(vec![
+                Point(0.0, 0.0, 0.0),
+                Point(4.0, 0.0, 0.0),
+                Point(0.0, 3.0, 0.0),
+            ])),
+        },
+    )
+}
+
+#[test]
+fn polygon_is_a_sequence_of_points() {
+    assert_eq!(
+        render("{% for p in triangle %}{{ p.x }};{% endfor %}").unwrap(),
+        "0.0;4.0;0.0;"
+    );
+    assert_eq!(render("{{ triangle | length }}").unwrap(), "3");
+    assert_eq!(render("{{ triangle[1] }}").unwrap(), "(4, 0, 0)");
+    assert_eq!(render("{{ triangle | last }}").unwrap(), "(0, 3, 0)");
+    assert_eq!(render("{{ triangle.area() }}").unwrap(), "6.0");
+    assert_eq!(render("{{ triangle.perimeter() }}").unwrap(), "12.0");
+}
+
+#[test]
+fn bounding_box() {
+    let bbox = BoundingBox::from_points(&[Point(1.0, 5.0, -1.0), Point(3.0, 2.0, 1.0)]).unwrap();
+    assert_eq!(bbox.min, Point(1.0, 2.0, -1.0));
+    assert_eq!(bbox.max, Point(3.0, 5.0, 1.0));
+    assert_eq!(bbox.area(), 6.0);
+    assert!(bbox.contains(&Point(2.0, 2.0, 0.0)));
+    assert!(!bbox.contains(&Point(0.0, 3.0, 0.0)));
+    assert_eq!(BoundingBox::from_points(&[]), None);
+
+    assert_eq!(
+        render("{% set b = triangle.bbox() %}{{ b.width }}x{{ b.height }} {{ b.contains(point(1, 1, 0)) }}")
+            .unwrap(),
+        "4.0x3.0 true"
+    );
+}
+
+#[test]
+fn constructors_accept_point_like_values() {
+    assert_eq!(
+        render("{{ polygon([[0, 0, 0], [2, 0, 0], {'x': 2, 'y': 2, 'z': 0}]).area() }}").unwrap(),
+        "2.0"
+    );
+    assert_eq!(
+        render("{{ path([[0, 0, 0], [3, 4, 0], [3, 4, 12]]).length() }}").unwrap(),
+        "17.0"
+    );
+    assert_eq!(
+        render("{{ path(triangle).closed().perimeter() }}").unwrap(),
+        "12.0"
+    );
+    assert_eq!(render("{{ bbox(triangle).max }}").unwrap(), "(4, 3, 0)");
+    assert_eq!(
+        Path(vec![Point(0.0, 0.0, 0.0), Point(0.0, 0.0, 2.0)]).length(),
+        2.0
+    );
+}
+
+#[test]
+fn errors() {
+    let err = render("{{ polygon(42) }}").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expects a sequence of points, got number"),
+        "{err}"
+    );
+    let err = render("{{ polygon([[0, 0, 0], 'x']) }}").unwrap_err();
+    assert!(err.to_string().contains("item 1 is not a point"), "{err}");
+    let err = render("{{ bbox([]) }}").unwrap_err();
+    assert!(err.to_string().contains("at least one point"), "{err}");
+    let err = render("{{ triangle.bbox().contains(1) }}").unwrap_err();
+    assert!(
+        err.to_string().contains("contains() expects a point"),
+        "{err}"
+    );
+    assert_eq!(
+        render("{{ triangle.area(1) }}").unwrap_err().kind(),
+        ErrorKind::TooManyArguments
+    );
+    assert_eq!(
+        render("{{ triangle.rotate(1) }}").unwrap_err().kind(),
+        ErrorKind::UnknownMethod
+    );
+    assert_eq!(
+        render("{{ path(triangle).length(1) }}").unwrap_err().kind(),
+        ErrorKind::TooManyArguments
+    );
+}
+
+#[test]
+fn empty_shapes_have_no_bbox() {
+    for source in [
+        "{{ bbox([]) }}",
+        "{{ polygon([]).bbox() }}",
+        "{{ path([]).bbox() }}",
+    ] {
+        let err = render(source).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidOperation, "{source}");
+        assert!(
+            err.to_string().contains("bbox() needs at least one point"),
+            "{source}: {err}"
+        );
+    }
+    let err = render("{{ polygon([]).centroid() }}").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("centroid() needs at least one point"),
+        "{err}"
+    );
+    assert_eq!(render("{{ polygon([]).area() }}").unwrap(), "0.0");
+}
@@ -0,0 +1,95 @@
+//! Renders every registered example and compares its output against the
+//! checked-in file in `tests/golden/<name>.txt`.
+//!
+//! Run with `UPDATE_GOLDEN=1 cargo test` to rewrite the golden files after
+//! an intentional change in behaviour.
+
+use minijinja_exploration::examples::{self, EXAMPLES};
+use std::fs;
+use std::path::PathBuf;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.txt"))
+}
+
+fn check_golden(name: &str) {
+    let example = match examples::find(name).as_slice() {
+        [example] => *example,
+        _ => panic!("no example named {name}"),
+    };
+    let mut output = Vec::new();
+    if let Err(err) = (example.run)(&mut output) {
+        panic!("example {name} failed: {err:#}");
+    }
+    let output = String::from_utf8(output).expect("example output is not UTF-8");
+
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &output).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "cannot read {}: {err}; run with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    assert_eq!(
+        output,
+        expected,
+        "output of {name} differs from {}",
+        path.display()
+    );
+}
+
+macro_rules! golden_tests {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check_golden(stringify!($name));
+            }
+        )*
+
+        #[test]
+        fn every_example_has_a_golden_test() {
+            let covered = [$(stringify!($name)),*];
+            for example in EXAMPLES {
+                assert!(
+                    covered.contains(&example.name),
+                    "example {} has no golden test",
+                    example.name
+                );
+            }
+        }
+    };
+}
+
+golden_tests!(
+    test_template_usage,
+    test_expression_usage,
+    test_dynamic_objects,
+    test_point_methods,
+    test_point_rendering,
+    test_points_from_context,
+    test_geometry_objects,
+    test_point_sorting,
+    test_custom_filters,
+    test_templates_iteration,
+    test_get_template_by_name,
+    test_loading_template_from_a_string,
+    test_parse_and_render_from_string_in_one_go,
+    test_render_and_return_evaluated_state,
+    test_discard_output_and_return_internal_state,
+    test_return_undeclared_variables,
+    test_custom_filters_example1_slugify,
+    test_unique_slugs,
+    test_context_info,
+    test_limits,
+    test_kwarg_handling,
+    test_reduce,
+    test_embedded_templates,
+);
@@ -0,0 +1,139 @@
+use minijinja::value::Value;
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::add_extensions;
+use minijinja_exploration::introspection::track_includes;
+use minijinja_exploration::point::Point;
+use std::collections::BTreeMap;
+
+fn env() -> Environment<'static> {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    env.add_template("layout.html", "{% block main %}{% endblock %}")
+        .unwrap();
+    env.add_template(
+        "index.html",
+        "{% extends 'layout.html' %}{% block main %}{{ debug() }}{% endblock %}",
+    )
+    .unwrap();
+    env.add_template("info.txt", "{{ context_info() | tojson }}")
+        .unwrap();
+    env
+}
+
+#[test]
+fn context_info_describes_the_render() {
+    let env = env();
+    let rv = env
+        .get_template("info.txt")
+        .unwrap()
+        .render(context! { n => 1, p => Value::from_object(Point(1.0, 2.0, 3.0)) })
+        .unwrap();
+    let info: serde_json::Value = serde_json::from_str(&rv).unwrap();
+    assert_eq!(info["template"], "info.txt");
+    assert_eq!(info["block"], serde_json::Value::Null);
+    assert_eq!(info["auto_escape"], "none");
+    // Only environments set up with `track_includes` know the stack.
+    assert_eq!(info["include_stack"], serde_json::Value::Null);
+    assert_eq!(
+        info["templates"],
+        serde_json::json!(["index.html", "info.txt", "layout.html"])
+    );
+    // Globals such as `range` or `point` are left out.
+    assert_eq!(
+        info["variables"],
+        serde_json::json!({"n": "number", "p": "point"})
+    );
+}
+
+#[test]
+fn debug_renders_an_escaped_html_table() {
+    let env = env();
+    let rv = env
+        .get_template("index.html")
+        .unwrap()
+        .render(context! { user => "<b>" })
+        .unwrap();
+    assert!(rv.starts_with("<table class=\"context-info\">"), "{rv}");
+    assert!(
+        rv.contains("<th>block</th><td colspan=\"2\">main</td>"),
+        "{rv}"
+    );
+    assert!(
+        rv.contains(
+            "<tr><td>user</td><td>string</td><td><code>&quot;&lt;b&gt;&quot;</code></td></tr>"
+        ),
+        "{rv}"
+    );
+}
+
+#[test]
+fn debug_formats() {
+    let env = env();
+    let rv = env
+        .render_str(
+            "{{ debug(format='text') }}",
+            context! { long => "x".repeat(100) },
+        )
+        .unwrap();
+    assert!(rv.starts_with("template: <string>\nblock: -\n"), "{rv}");
+    assert!(
+        rv.contains(&format!("long      string  \"{}...", "x".repeat(59))),
+        "{rv}"
+    );
+
+    let err = env.render_str("{{ debug(format='xml') }}", ()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("debug: invalid value for `format`: unknown variant `xml`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn macros_are_told_apart_from_lookalike_maps() {
+    let env = env();
+    let rv = env
+        .render_str(
+            "{% macro m(a) %}{% endmacro %}\
+             {% set fake = {'name': 'm', 'arguments': ['a'], 'caller': false} %}\
+             {{ context_info().variables | tojson }}",
+            (),
+        )
+        .unwrap();
+    let variables: serde_json::Value = serde_json::from_str(&rv).unwrap();
+    assert_eq!(variables, serde_json::json!({"fake": "map", "m": "macro"}));
+}
+
+#[test]
+fn tracked_loaders_know_the_include_stack() {
+    let sources = BTreeMap::from([
+        (
+            "page.html",
+            "{% extends 'layout.html' %}{% block main %}{% include 'nav.html' %}{% endblock %}",
+        ),
+        ("layout.html", "<main>{% block main %}{% endblock %}</main>"),
+        (
+            "nav.html",
+            "{% for item in ['a'] %}{% include 'item' ~ '.html' %}{% endfor %}",
+        ),
+        (
+            "item.html",
+            "{{ context_info().include_stack | join(' > ') | safe }}",
+        ),
+    ]);
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    let log = track_includes(&mut env, move |name| {
+        Ok(sources.get(name).map(|source| source.to_string()))
+    });
+    assert_eq!(
+        env.get_template("page.html").unwrap().render(()).unwrap(),
+        "<main>page.html > nav.html > item.html</main>"
+    );
+    assert_eq!(log.stack("nav.html"), ["page.html", "nav.html"]);
+    assert_eq!(log.stack("layout.html"), ["page.html", "layout.html"]);
+
+    let rv = env.render_str("{{ debug(format='text') }}", ()).unwrap();
+    assert!(rv.contains("\ninclude stack: <string>\n"), "{rv}");
+}
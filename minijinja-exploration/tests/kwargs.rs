@@ -0,0 +1,148 @@
+use minijinja::value::{Kwargs, Value};
+use minijinja::{Environment, Error, ErrorKind};
+use minijinja_exploration::kwargs::from_kwargs;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Options {
+    name: String,
+    #[serde(default)]
+    count: u32,
+    #[serde(default = "default_sep")]
+    sep: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    range: Option<Range>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Range {
+    start: u32,
+    end: u32,
+}
+
+fn default_sep() -> String {
+    ", ".into()
+}
+
+fn parse(kwargs: &[(&str, Value)]) -> Result<Options, Error> {
+    from_kwargs("opts", &Kwargs::from_iter(kwargs.iter().cloned()))
+}
+
+#[test]
+fn defaults_fill_in_missing_options() {
+    assert_eq!(
+        parse(&[("name", Value::from("x"))]).unwrap(),
+        Options {
+            name: "x".into(),
+            count: 0,
+            sep: ", ".into(),
+            tags: vec![],
+            range: None,
+        }
+    );
+    assert_eq!(
+        parse(&[
+            ("name", Value::from("x")),
+            ("count", Value::from(3)),
+            ("sep", Value::from("-")),
+            ("tags", Value::from(vec!["a", "b"])),
+        ])
+        .unwrap(),
+        Options {
+            name: "x".into(),
+            count: 3,
+            sep: "-".into(),
+            tags: vec!["a".into(), "b".into()],
+            range: None,
+        }
+    );
+}
+
+#[test]
+fn errors_name_the_argument() {
+    let err = parse(&[("name", Value::from("x")), ("cuont", Value::from(3))]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TooManyArguments);
+    assert!(
+        err.to_string()
+            .contains("opts: unknown field `cuont`, expected one of"),
+        "{err}"
+    );
+
+    let err = parse(&[("count", Value::from(3))]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+    assert!(
+        err.to_string().contains("opts: missing field `name`"),
+        "{err}"
+    );
+
+    let err = parse(&[("name", Value::from("x")), ("count", Value::from(-1))]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string().contains("opts: invalid value for `count`"),
+        "{err}"
+    );
+
+    let err = parse(&[
+        ("name", Value::from("x")),
+        ("tags", Value::from(vec![Value::from("a"), Value::from(1)])),
+    ])
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("opts: invalid value for `tags[1]`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn nested_errors_are_invalid_values() {
+    let range = |pairs: &[(&str, u32)]| Value::from_iter(pairs.iter().copied());
+    let err = parse(&[
+        ("name", Value::from("x")),
+        ("range", range(&[("start", 1), ("end", 2), ("step", 1)])),
+    ])
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("opts: invalid value for `range.step`: unknown field `step`"),
+        "{err}"
+    );
+
+    let err = parse(&[
+        ("name", Value::from("x")),
+        ("range", range(&[("start", 1)])),
+    ])
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("opts: invalid value for `range`: missing field `end`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn works_from_templates() {
+    let mut env = Environment::new();
+    env.add_function("greet", |kwargs: Kwargs| {
+        let options: Options = from_kwargs("greet", &kwargs)?;
+        Ok::<_, Error>(options.name.repeat(options.count as usize))
+    });
+    assert_eq!(
+        env.render_str("{{ greet(name='ab', count=2) }}", ())
+            .unwrap(),
+        "abab"
+    );
+    let err = env
+        .render_str("{{ greet(name='ab', count='2') }}", ())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("greet: invalid value for `count`"),
+        "{err}"
+    );
+}
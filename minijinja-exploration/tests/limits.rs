@@ -0,0 +1,69 @@
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::add_extensions;
+use minijinja_exploration::limits::{DEFAULT_MAX_OUTPUT, Limits, repeat};
+
+fn env(limits: &Limits) -> Environment<'static> {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    limits.apply(&mut env);
+    env
+}
+
+#[test]
+fn repeat_is_bounded() {
+    assert_eq!(repeat("ab", 3, 6).unwrap(), "ababab");
+    let err = repeat("ab", 4, 6).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(err.to_string().contains("limit of 6 bytes"), "{err}");
+    // The length computation must not overflow either.
+    assert!(repeat("ab", usize::MAX, usize::MAX).is_err());
+
+    // `add_extensions` alone already bounds `repeat`.
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    let err = env
+        .render_str(
+            "{{ 'x' | repeat(n) }}",
+            context! { n => DEFAULT_MAX_OUTPUT + 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+}
+
+#[test]
+fn output_budget() {
+    let limits = Limits {
+        fuel: None,
+        max_output: 10,
+    };
+    let env = env(&limits);
+    let tmpl = env
+        .template_from_str("{% for i in range(n) %}{{ i }}{% endfor %}")
+        .unwrap();
+    assert_eq!(
+        limits.render(&tmpl, context! { n => 10 }).unwrap(),
+        "0123456789"
+    );
+    let err = limits.render(&tmpl, context! { n => 11 }).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("rendered output exceeds the output limit of 10 bytes"),
+        "{err}"
+    );
+}
+
+#[test]
+fn fuel_limit() {
+    let limits = Limits {
+        fuel: Some(500),
+        ..Limits::default()
+    };
+    let env = env(&limits);
+    let tmpl = env
+        .template_from_str("{% for i in range(n) %}{% endfor %}done")
+        .unwrap();
+    assert_eq!(limits.render(&tmpl, context! { n => 10 }).unwrap(), "done");
+    let err = limits.render(&tmpl, context! { n => 10000 }).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfFuel);
+}
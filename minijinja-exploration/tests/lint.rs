@@ -0,0 +1,78 @@
+use minijinja_exploration::commands::lint::{Schema, Severity, lint_dir};
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/lint");
+
+fn lint(schema_file: &str) -> Vec<String> {
+    let json = std::fs::read_to_string(format!("{FIXTURES}/{schema_file}")).unwrap();
+    let schema = Schema::from_json(&serde_json::from_str(&json).unwrap()).unwrap();
+    let dir = Path::new(FIXTURES).join("templates");
+    lint_dir(&dir, &schema, schema_file)
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            diagnostic
+                .to_string()
+                .replace(&format!("{}/", dir.display()), "")
+        })
+        .collect()
+}
+
+#[test]
+fn json_schema_reports_missing_misspelled_and_unused() {
+    assert_eq!(
+        lint("schema.json"),
+        [
+            "page.html:5:7: error: unknown variable `user.nmae`, did you mean `user.name`?",
+            "page.html:6:4: error: variable `footer` is not declared in the schema",
+            "schema.json: warning: variable `theme` is declared but never used",
+            "schema.json: warning: variable `user.email` is declared but never used",
+        ]
+    );
+}
+
+#[test]
+fn manifest_allows_everything_below_a_declared_path() {
+    assert_eq!(
+        lint("manifest.json"),
+        ["page.html:5:7: error: unknown variable `user.nmae`, did you mean `user.name`?"]
+    );
+}
+
+#[test]
+fn schema_paths() {
+    let schema = Schema::from_json(&serde_json::json!({
+        "variables": ["config", "user.name"]
+    }))
+    .unwrap();
+    assert!(schema.allows("config.deeply.nested"));
+    assert!(schema.allows("user"));
+    assert!(schema.allows("user.name"));
+    assert!(!schema.allows("user.email"));
+    assert!(!schema.allows("configuration"));
+    assert_eq!(schema.suggest("usr.name"), Some("user.name"));
+    assert_eq!(schema.suggest("completely_different"), None);
+}
+
+#[test]
+fn syntax_errors_are_reported_with_their_line() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("broken.txt"), "ok\n{{ oops ").unwrap();
+    let schema = Schema::from_json(&serde_json::json!({ "variables": [] })).unwrap();
+    let diagnostics = lint_dir(dir.path(), &schema, "schema.json").unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].location, Some((2, 1)));
+}
+
+#[test]
+fn line_numbers_past_u16_do_not_wrap() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = format!("{}{{{{ oops ", "\n".repeat(70_000));
+    std::fs::write(dir.path().join("long.txt"), source).unwrap();
+    let schema = Schema::from_json(&serde_json::json!({ "variables": [] })).unwrap();
+    let diagnostics = lint_dir(dir.path(), &schema, "schema.json").unwrap();
+    // minijinja saturates its own line numbers at `u16::MAX`.
+    let (line, _) = diagnostics[0].location.unwrap();
+    assert!(line >= usize::from(u16::MAX), "line {line}");
+}
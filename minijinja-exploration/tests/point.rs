@@ -0,0 +1,235 @@
+use minijinja::value::Value;
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::point::{Point, convert_points, format_point, point};
+use std::hash::BuildHasher;
+
+fn eval(expr: &str) -> Result<Value, minijinja::Error> {
+    let env = Environment::new();
+    env.compile_expression(expr)?.eval(context! {
+        p => Value::from_object(Point(1.0, 2.0, 2.0)),
+        q => Value::from_object(Point(0.0, 1.0, 0.0)),
+        origin => Value::from_object(Point(0.0, 0.0, 0.0)),
+    })
+}
+
+#[test]
+fn vector_methods() {
+    assert_eq!(eval("p.length()").unwrap(), Value::from(3.0));
+    assert_eq!(eval("p.dot(q)").unwrap(), Value::from(2.0));
+    assert_eq!(eval("p.distance(p)").unwrap(), Value::from(0.0));
+    let cross = eval("p.cross(q)").unwrap();
+    assert_eq!(
+        cross.downcast_object_ref::<Point>(),
+        Some(&Point(-2.0, 0.0, 1.0))
+    );
+    let scaled = eval("p.scale(0.5)").unwrap();
+    assert_eq!(
+        scaled.downcast_object_ref::<Point>(),
+        Some(&Point(0.5, 1.0, 1.0))
+    );
+    assert_eq!(
+        eval("p.normalize().length() | round(6)").unwrap(),
+        Value::from(1.0)
+    );
+}
+
+#[test]
+fn wrong_arguments_are_errors() {
+    let err = eval("p.dot(3)").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("dot() expects a point, got number"),
+        "{err}"
+    );
+
+    assert_eq!(
+        eval("p.dot()").unwrap_err().kind(),
+        ErrorKind::MissingArgument
+    );
+    assert_eq!(
+        eval("p.length(q)").unwrap_err().kind(),
+        ErrorKind::TooManyArguments
+    );
+    assert_eq!(
+        eval("p.scale('x')").unwrap_err().kind(),
+        ErrorKind::InvalidOperation
+    );
+
+    let err = eval("origin.normalize()").unwrap_err();
+    assert!(err.to_string().contains("length 0"), "{err}");
+
+    assert_eq!(
+        eval("p.rotate()").unwrap_err().kind(),
+        ErrorKind::UnknownMethod
+    );
+}
+
+#[test]
+fn format_point_options() {
+    let mut env = Environment::new();
+    env.add_filter("format_point", format_point);
+    let render = |source: &str| {
+        env.render_str(
+            source,
+            context!(p => Value::from_object(Point(1.0, 2.5, -3.0))),
+        )
+    };
+    assert_eq!(render("{{ p }}").unwrap(), "(1, 2.5, -3)");
+    assert_eq!(
+        render("{{ p | format_point(precision=3) }}").unwrap(),
+        "(1.000, 2.500, -3.000)"
+    );
+    assert_eq!(
+        render("{{ p | format_point(style='json') }}").unwrap(),
+        r#"{"x": 1, "y": 2.5, "z": -3}"#
+    );
+
+    let err = render("{{ p | format_point(style='yaml') }}").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("format_point: invalid value for `style`: unknown variant `yaml`"),
+        "{err}"
+    );
+    let err = render("{{ p | format_point(digits=2) }}").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TooManyArguments);
+    let err = render("{{ 42 | format_point }}").unwrap_err();
+    assert!(
+        err.to_string().contains("expects a point, got number"),
+        "{err}"
+    );
+}
+
+#[test]
+fn serializes_as_map() {
+    let env = Environment::new();
+    let p = Value::from_object(Point(1.0, 2.5, 3.0));
+    assert_eq!(
+        env.render_str("{{ p | tojson }}", context!(p)).unwrap(),
+        r#"{"x":1.0,"y":2.5,"z":3.0}"#
+    );
+    assert_eq!(
+        serde_json::to_value(&p).unwrap(),
+        serde_json::json!({"x": 1.0, "y": 2.5, "z": 3.0})
+    );
+}
+
+#[test]
+fn point_function() {
+    let mut env = Environment::new();
+    env.add_function("point", point);
+    let eval = |expr: &str| env.compile_expression(expr).unwrap().eval(context! {});
+    assert_eq!(eval("point(1, 2.5, 3)").unwrap().to_string(), "(1, 2.5, 3)");
+    assert_eq!(eval("point([1, 2, 3])").unwrap().to_string(), "(1, 2, 3)");
+    assert_eq!(
+        eval("point({'x': 1, 'y': 2, 'z': 3}).length() > 3").unwrap(),
+        Value::from(true)
+    );
+    assert_eq!(
+        eval("point(point(1, 2, 3)) == point(1, 2, 3)").unwrap(),
+        Value::from(true)
+    );
+
+    let err = eval("point(1, 'a', 3)").unwrap_err();
+    assert!(
+        err.to_string().contains("must be numbers, got string"),
+        "{err}"
+    );
+    let err = eval("point([1, 2])").unwrap_err();
+    assert!(err.to_string().contains("cannot convert sequence"), "{err}");
+    let err = eval("point(1, 2)").unwrap_err();
+    assert!(err.to_string().contains("got 2 arguments"), "{err}");
+}
+
+#[test]
+fn converts_nested_json() {
+    let ctx = convert_points(Value::from_serialize(serde_json::json!({
+        "origin": [0, 0, 0],
+        "named": {"x": 1, "y": 2, "z": 3},
+        "shapes": [{"corners": [[1, 1, 1], {"x": 2, "y": 2, "z": 2}]}],
+        "not_points": {
+            "pair": [1, 2],
+            "words": ["a", "b", "c"],
+            "extra": {"x": 1, "y": 2, "z": 3, "w": 4},
+        },
+    })));
+    let is_point = |path: &str| {
+        let mut value = ctx.clone();
+        for part in path.split('.') {
+            value = match part.parse::<usize>() {
+                Ok(idx) => value.get_item_by_index(idx).unwrap(),
+                Err(_) => value.get_attr(part).unwrap(),
+            };
+        }
+        value.downcast_object_ref::<Point>().is_some()
+    };
+    assert!(is_point("origin"));
+    assert!(is_point("named"));
+    assert!(is_point("shapes.0.corners.0"));
+    assert!(is_point("shapes.0.corners.1"));
+    assert!(!is_point("not_points.pair"));
+    assert!(!is_point("not_points.words"));
+    assert!(!is_point("not_points.extra"));
+}
+
+#[test]
+fn points_compare_by_value() {
+    assert_eq!(eval("p == p.scale(1)").unwrap(), Value::from(true));
+    assert_eq!(eval("p != q").unwrap(), Value::from(true));
+    assert_eq!(eval("origin < q and q < p").unwrap(), Value::from(true));
+    assert_eq!(
+        Point(1.0, 2.0, 3.0).total_cmp(&Point(1.0, 3.0, 0.0)),
+        std::cmp::Ordering::Less
+    );
+
+    let a = Value::from_object(Point(1.0, 2.0, 3.0));
+    let b = Value::from_object(Point(1.0, 2.0, 3.0));
+    let state = std::hash::RandomState::new();
+    assert_eq!(a, b);
+    assert_eq!(state.hash_one(&a), state.hash_one(&b));
+}
+
+#[test]
+fn negative_zero_equals_zero() {
+    assert_eq!(
+        eval("origin == origin.scale(-1)").unwrap(),
+        Value::from(true)
+    );
+    assert_eq!(
+        eval("[origin, origin.scale(-1)] | unique | list | length").unwrap(),
+        Value::from(1)
+    );
+    assert_eq!(
+        eval("origin.scale(-1) | string").unwrap(),
+        Value::from("(0, 0, 0)")
+    );
+
+    // Even points built by hand with `-0.0` compare and hash like `0.0`.
+    let a = Value::from_object(Point(-0.0, 0.0, -0.0));
+    let b = Value::from_object(Point(0.0, -0.0, 0.0));
+    let state = std::hash::RandomState::new();
+    assert_eq!(a, b);
+    assert_eq!(state.hash_one(&a), state.hash_one(&b));
+    assert_eq!(Point::new(-0.0, 1.0, -2.0), Point(0.0, 1.0, -2.0));
+    assert!(Point::new(-0.0, 0.0, 0.0).0.is_sign_positive());
+}
+
+#[test]
+fn points_sort_and_deduplicate() {
+    let mut env = Environment::new();
+    env.add_function("point", point);
+    let expr = env
+        .compile_expression(
+            "[p, q, origin, point(0, 1, 0)] | unique | sort | map('string') | join(' ')",
+        )
+        .unwrap();
+    let ctx = context! {
+        p => Value::from_object(Point(1.0, 2.0, 2.0)),
+        q => Value::from_object(Point(0.0, 1.0, 0.0)),
+        origin => Value::from_object(Point(0.0, 0.0, 0.0)),
+    };
+    assert_eq!(
+        expr.eval(ctx).unwrap().to_string(),
+        "(0, 0, 0) (0, 1, 0) (1, 2, 2)"
+    );
+}
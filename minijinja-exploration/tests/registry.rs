@@ -0,0 +1,82 @@
+use minijinja::{Environment, ErrorKind};
+use minijinja_exploration::add_extensions;
+use minijinja_exploration::registry::{ENTRIES, Kind, find, listing, register};
+use std::collections::HashSet;
+
+#[test]
+fn examples_render_as_documented() {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    for entry in ENTRIES {
+        env.add_template("example.txt", entry.example).unwrap();
+        let rendered = env
+            .get_template("example.txt")
+            .and_then(|tmpl| tmpl.render(()))
+            .unwrap_or_else(|err| panic!("{}: {err:#}", entry.name));
+        assert_eq!(rendered, entry.example_output, "{}", entry.name);
+    }
+}
+
+#[test]
+fn every_entry_is_registered_once() {
+    let mut names = HashSet::new();
+    for entry in ENTRIES {
+        assert!(names.insert(entry.name), "{} is listed twice", entry.name);
+        assert!(entry.signature.contains(entry.name), "{}", entry.name);
+        for (kwarg, _) in entry.kwargs {
+            assert!(entry.signature.contains(kwarg), "{}: {kwarg}", entry.name);
+        }
+    }
+
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    for entry in ENTRIES.iter().filter(|entry| entry.kind == Kind::Function) {
+        assert!(
+            env.globals().any(|(name, _)| name == entry.name),
+            "{}",
+            entry.name
+        );
+    }
+}
+
+#[test]
+fn help_documents_filters_and_functions() {
+    let mut env = Environment::new();
+    add_extensions(&mut env);
+    let rendered = env.render_str("{{ help('slugify') }}", ()).unwrap();
+    assert_eq!(rendered, find("slugify").unwrap().help());
+    assert!(rendered.starts_with("slugify (filter)\n"));
+    assert!(rendered.contains("\nKeyword arguments:\n    separator   "));
+    assert!(rendered.ends_with("=> hello-world"));
+
+    let rendered = env.render_str("{{ help() }}", ()).unwrap();
+    assert_eq!(rendered, listing(&[Kind::Filter, Kind::Function]));
+    assert!(rendered.starts_with("filters:\n  value | repeat(n)\n"));
+    assert!(rendered.contains("\nfunctions:\n  modify(values, "));
+
+    let err = env.render_str("{{ help('nope') }}", ()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("help: no filter or function named `nope`")
+    );
+}
+
+#[test]
+fn register_adds_single_entries() {
+    let mut env = Environment::new();
+    register(&mut env, &["repeat", "point"]).unwrap();
+    assert_eq!(
+        env.render_str("{{ 'ab' | repeat(2) }} {{ point(1, 2, 3) }}", ())
+            .unwrap(),
+        "abab (1, 2, 3)"
+    );
+    assert!(env.render_str("{{ 'a' | slugify }}", ()).is_err());
+
+    let err = register(&mut env, &["nope"]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("no filter or function named `nope`"),
+        "{err}"
+    );
+}
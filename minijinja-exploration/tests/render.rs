@@ -0,0 +1,124 @@
+use minijinja_exploration::commands::{UsageError, render};
+use std::fs;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/render");
+
+fn run(args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    render::run(&args)
+}
+
+#[test]
+fn renders_to_file_with_merged_contexts() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.txt");
+    run(&[
+        "--templates",
+        FIXTURES,
+        "--context",
+        &format!("{FIXTURES}/defaults.json"),
+        "--context",
+        &format!("{FIXTURES}/override.json"),
+        "--output",
+        output.to_str().unwrap(),
+        "greeting.txt",
+    ])
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(output).unwrap(),
+        "== Hello Grace from London! ==\n"
+    );
+}
+
+#[test]
+fn rejects_non_object_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let context = dir.path().join("list.json");
+    fs::write(&context, "[1, 2, 3]").unwrap();
+    let err = run(&[
+        "--templates",
+        FIXTURES,
+        "--context",
+        context.to_str().unwrap(),
+        "greeting.txt",
+    ])
+    .unwrap_err();
+    assert!(err.to_string().contains("must be a JSON object"), "{err}");
+}
+
+#[test]
+fn missing_template_dir_is_a_usage_error() {
+    let err = run(&["greeting.txt"]).unwrap_err();
+    assert!(err.is::<UsageError>());
+}
+
+#[test]
+fn points_flag_converts_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.txt");
+    run(&[
+        "--templates",
+        FIXTURES,
+        "--context",
+        &format!("{FIXTURES}/points.json"),
+        "--output",
+        output.to_str().unwrap(),
+        "--points",
+        "points.txt",
+    ])
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(output).unwrap(),
+        "(0, 0, 0) (0.0, 0.0, 0.0)\n(1, 2, 3) (1.0, 2.0, 3.0)\n\n"
+    );
+}
+
+#[test]
+fn layered_template_dirs() {
+    let theme = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/theme");
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out.html");
+    let context = dir.path().join("context.json");
+    fs::write(&context, r#"{"brand": "Initech"}"#).unwrap();
+    run(&[
+        "--templates",
+        &format!("{theme}/project"),
+        "--templates",
+        &format!("{theme}/acme"),
+        "--templates",
+        &format!("defaults={theme}/defaults"),
+        "--context",
+        context.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+        "404.html",
+    ])
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(output).unwrap(),
+        "<header>ACME</header>\n<main>Page not found.</main>\n\
+         <footer>Brought to you by Initech. Powered by defaults</footer>\n"
+    );
+
+    let loader = render::theme_loader([&*format!("{theme}/acme"), "base=templates"]).unwrap();
+    let layers: Vec<_> = loader.layers().map(|(name, _)| name).collect();
+    assert_eq!(layers, ["acme", "base"]);
+}
+
+#[test]
+fn duplicate_layer_names_are_a_usage_error() {
+    let err = run(&[
+        "--templates",
+        "proj/templates",
+        "--templates",
+        "theme/templates",
+        "index.html",
+    ])
+    .unwrap_err();
+    let err = err.downcast::<UsageError>().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "--templates theme/templates: `templates` is used for more than one layer, use LAYER=DIR"
+    );
+    assert!(render::theme_loader(["proj=proj/templates", "theme/templates"]).is_ok());
+}
@@ -0,0 +1,61 @@
+use minijinja_exploration::commands::repl::{Outcome, Repl};
+
+fn print(repl: &mut Repl, line: &str) -> String {
+    match repl.handle_line(line).unwrap() {
+        Outcome::Print(text) => text,
+        other => panic!("expected output for {line:?}, got {other:?}"),
+    }
+}
+
+#[test]
+fn evaluates_against_set_variables() {
+    let mut repl = Repl::new();
+    assert_eq!(print(&mut repl, ":set number = 23"), "number = 23 (number)");
+    assert_eq!(print(&mut repl, "number < 42"), "true (bool)");
+    assert_eq!(
+        repl.handle_line(":unset number").unwrap(),
+        Outcome::Continue
+    );
+    assert!(repl.vars().is_empty());
+}
+
+#[test]
+fn crate_filters_and_functions_are_available() {
+    let mut repl = Repl::new();
+    assert_eq!(
+        print(&mut repl, "'John Wild Oak' | slugify"),
+        "\"john-wild-oak\" (string)"
+    );
+    assert_eq!(print(&mut repl, "'ab' | repeat(2)"), "\"abab\" (string)");
+    assert_eq!(
+        print(&mut repl, "modify([1, 2, 3], reverse=true)"),
+        "[3, 2, 1] (sequence)"
+    );
+    assert_eq!(
+        print(&mut repl, "mathematical_fold(1, 2, 3, 4, op='mul')"),
+        "24 (number)"
+    );
+}
+
+#[test]
+fn loads_json_context() {
+    let mut repl = Repl::new();
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/render/defaults.json"
+    );
+    repl.handle_line(&format!(":load {path}")).unwrap();
+    assert_eq!(
+        print(&mut repl, "name ~ ' in ' ~ place"),
+        "\"Ada in London\" (string)"
+    );
+}
+
+#[test]
+fn reports_errors_and_quits() {
+    let mut repl = Repl::new();
+    assert!(repl.handle_line("1 +").is_err());
+    assert!(repl.handle_line(":nope").is_err());
+    assert!(repl.handle_line(":set 1x = 2").is_err());
+    assert_eq!(repl.handle_line(":quit").unwrap(), Outcome::Quit);
+}
@@ -0,0 +1,183 @@
+#![cfg(unix)]
+
+use minijinja::value::Value;
+use minijinja::{ErrorKind, context};
+use minijinja_exploration::limits::Limits;
+use minijinja_exploration::sandbox::{Sandbox, SandboxConfig, sandboxed_environment};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn render_err(sandbox: &Sandbox, source: &str) -> minijinja::Error {
+    sandbox
+        .render_str(source, context! {})
+        .expect_err("template should be rejected")
+}
+
+#[test]
+fn trusted_templates_still_render() {
+    let mut sandbox = sandboxed_environment();
+    sandbox
+        .add_template("page", "{{ title | slugify }}: {{ point(1, 2, 3) }}")
+        .unwrap();
+    assert_eq!(
+        sandbox
+            .render("page", context! { title => "Hello World" })
+            .unwrap(),
+        "hello-world: (1, 2, 3)"
+    );
+}
+
+#[test]
+fn endless_loops_run_out_of_fuel() {
+    let sandbox = sandboxed_environment();
+    let err = render_err(
+        &sandbox,
+        "{% for i in range(100000) %}{% for j in range(100000) %}{% endfor %}{% endfor %}",
+    );
+    assert_eq!(err.kind(), ErrorKind::OutOfFuel);
+}
+
+#[test]
+fn deep_recursion_is_rejected() {
+    let sandbox = sandboxed_environment();
+    let err = render_err(
+        &sandbox,
+        "{% macro down(n) %}{{ down(n + 1) }}{% endmacro %}{{ down(0) }}",
+    );
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string().contains("recursion limit exceeded"),
+        "{err}"
+    );
+
+    let mut sandbox = sandboxed_environment();
+    sandbox
+        .add_template("self", "{% include 'self' %}")
+        .unwrap();
+    let err = sandbox.render("self", context! {}).unwrap_err();
+    // Each include wraps the error of the template it included.
+    let innermost = std::iter::successors(Some(&err as &dyn std::error::Error), |err| err.source())
+        .last()
+        .unwrap();
+    assert!(
+        innermost.to_string().contains("recursion limit exceeded"),
+        "{innermost}"
+    );
+}
+
+#[test]
+fn huge_output_is_rejected() {
+    let sandbox = sandboxed_environment();
+    let err = render_err(&sandbox, "{{ 'x' | repeat(1000000000) }}");
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string().contains("exceeds the output limit"),
+        "{err}"
+    );
+
+    let err = render_err(
+        &sandbox,
+        "{% for i in range(1000) %}{{ 'x' | repeat(10000) }}{% endfor %}",
+    );
+    assert!(
+        err.to_string()
+            .contains("rendered output exceeds the output limit"),
+        "{err}"
+    );
+}
+
+#[test]
+fn huge_strings_are_contained() {
+    let sandbox = sandboxed_environment();
+    // Folded into a constant while the template is compiled.
+    let err = render_err(&sandbox, "{{ ('x' * 10000000000) | length }}");
+    assert!(err.to_string().contains("bytes of memory"), "{err}");
+    let mut sandbox = sandboxed_environment();
+    let err = sandbox
+        .add_template("huge", "{{ ('x' * 10000000000) | length }}")
+        .unwrap_err();
+    assert!(err.to_string().contains("bytes of memory"), "{err}");
+
+    let err = sandbox
+        .render_str(
+            "{{ ('x' * n) | length }}",
+            context! { n => 10_000_000_000u64 },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("bytes of memory"), "{err}");
+
+    let doubling = format!(
+        "{{% set s = 'x' %}}{}{{{{ s | length }}}}",
+        "{% set s = s ~ s %}".repeat(40)
+    );
+    let err = render_err(&sandbox, &doubling);
+    assert!(err.to_string().contains("bytes of memory"), "{err}");
+
+    // The sandbox is still usable afterwards.
+    assert_eq!(
+        sandbox.render_str("{{ 'ab' * 2 }}", context! {}).unwrap(),
+        "abab"
+    );
+}
+
+#[test]
+fn slow_renders_time_out() {
+    let sandbox = Sandbox::new(SandboxConfig {
+        timeout: Duration::from_millis(100),
+        ..SandboxConfig::default()
+    })
+    .unwrap();
+    // The render is killed when the timeout is reached.
+    let slow = Value::from_function(|| thread::sleep(Duration::from_millis(300)));
+    let started = Instant::now();
+    let err = sandbox
+        .render_str("{{ slow() }}", context! { slow })
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_millis(300));
+    assert!(err.to_string().contains("took longer than 100ms"), "{err}");
+}
+
+#[test]
+fn sandboxes_need_fuel() {
+    let err = Sandbox::new(SandboxConfig {
+        limits: Limits {
+            fuel: None,
+            ..Limits::default()
+        },
+        ..SandboxConfig::default()
+    })
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(err.to_string().contains("needs a fuel limit"), "{err}");
+}
+
+#[test]
+fn only_allowed_filters_and_functions_are_available() {
+    let sandbox = sandboxed_environment();
+    for source in [
+        "{{ modify([1, 2], reverse=true) }}",
+        "{{ mathematical_fold(1, 2, 'add') }}",
+        "{{ debug() }}",
+        "{{ context_info() }}",
+    ] {
+        let err = render_err(&sandbox, source);
+        assert_eq!(err.kind(), ErrorKind::UnknownFunction, "{source}");
+    }
+    let err = render_err(&sandbox, "{{ 'x' | append_template }}");
+    assert_eq!(err.kind(), ErrorKind::UnknownFilter);
+
+    let sandbox = Sandbox::new(SandboxConfig {
+        allowed: vec!["modify".into()],
+        ..SandboxConfig::default()
+    })
+    .unwrap();
+    assert_eq!(
+        sandbox
+            .render_str("{{ modify([1, 2], reverse=true) }}", context! {})
+            .unwrap(),
+        "[2, 1]"
+    );
+    let err = render_err(&sandbox, "{{ 'a b' | slugify }}");
+    assert_eq!(err.kind(), ErrorKind::UnknownFilter);
+}
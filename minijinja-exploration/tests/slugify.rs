@@ -0,0 +1,162 @@
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::filters::{SlugOptions, slug, slugify, unique_slug, unique_slugs};
+use proptest::prelude::*;
+
+fn render(template: &str, title: &str) -> Result<String, minijinja::Error> {
+    let mut env = Environment::new();
+    env.add_filter("slugify", slugify);
+    env.render_str(template, context! { title })
+}
+
+#[test]
+fn transliterates_and_strips_punctuation() {
+    let slugify = |value| slug(value, &SlugOptions::default());
+    assert_eq!(slugify("Crème Brûlée!"), "creme-brulee");
+    assert_eq!(slugify("  Hello,   World...  "), "hello-world");
+    assert_eq!(
+        slugify("Don’t stop -- rock 'n' roll"),
+        "dont-stop-rock-n-roll"
+    );
+    assert_eq!(slugify("Straße 42"), "strasse-42");
+    assert_eq!(slugify("北京"), "bei-jing");
+    assert_eq!(slugify("!!!"), "");
+}
+
+#[test]
+fn keyword_arguments() {
+    let title = "Crème Brûlée for Two";
+    assert_eq!(
+        render("{{ title | slugify(separator='_') }}", title).unwrap(),
+        "creme_brulee_for_two"
+    );
+    assert_eq!(
+        render("{{ title | slugify(lowercase=false) }}", title).unwrap(),
+        "Creme-Brulee-for-Two"
+    );
+    // Cut after the last word that fits, truncate a single long word.
+    assert_eq!(
+        render("{{ title | slugify(max_length=16) }}", title).unwrap(),
+        "creme-brulee-for"
+    );
+    assert_eq!(
+        render("{{ title | slugify(max_length=15) }}", title).unwrap(),
+        "creme-brulee"
+    );
+    assert_eq!(
+        render("{{ title | slugify(max_length=3) }}", title).unwrap(),
+        "cre"
+    );
+
+    let err = render("{{ title | slugify(sep='_') }}", title).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TooManyArguments);
+}
+
+#[test]
+fn unique_slugs_are_per_render() {
+    let mut env = Environment::new();
+    env.add_filter("unique_slug", unique_slug);
+    let tmpl = env
+        .template_from_str("{% for t in titles %}{{ t | unique_slug(separator='_') }} {% endfor %}")
+        .unwrap();
+    let ctx = context! { titles => ["A b", "a B", "a_b_2", "A B"] };
+
+    let (rv, state) = tmpl.render_and_return_state(&ctx).unwrap();
+    assert_eq!(rv, "a_b a_b_2 a_b_2_2 a_b_3 ");
+    assert_eq!(
+        unique_slugs(&state),
+        [
+            ("a_b".to_string(), "A b".to_string()),
+            ("a_b_2".to_string(), "a B".to_string()),
+            ("a_b_2_2".to_string(), "a_b_2".to_string()),
+            ("a_b_3".to_string(), "A B".to_string()),
+        ]
+    );
+
+    // A new render starts from scratch.
+    assert_eq!(tmpl.render(&ctx).unwrap(), rv);
+    let empty = env.template_from_str("no slugs").unwrap();
+    let (_, state) = empty.render_and_return_state(()).unwrap();
+    assert!(unique_slugs(&state).is_empty());
+}
+
+#[test]
+fn unique_slugs_respect_max_length() {
+    let mut env = Environment::new();
+    env.add_filter("unique_slug", unique_slug);
+    let render = |source: &str| env.render_str(source, ()).unwrap();
+    assert_eq!(
+        render("{% for i in range(11) %}{{ 'usage' | unique_slug(max_length=5) }} {% endfor %}"),
+        "usage usa-2 usa-3 usa-4 usa-5 usa-6 usa-7 usa-8 usa-9 us-10 us-11 "
+    );
+    // The shortened base doesn't end in a separator.
+    assert_eq!(
+        render("{% for i in range(2) %}{{ 'ab cd' | unique_slug(max_length=5) }} {% endfor %}"),
+        "ab-cd ab-2 "
+    );
+}
+
+#[test]
+fn unique_slugs_fail_when_max_length_is_too_short() {
+    let mut env = Environment::new();
+    env.add_filter("unique_slug", unique_slug);
+    let render = |source: &str| env.render_str(source, ());
+    assert_eq!(
+        render("{% for i in range(9) %}{{ 'abc' | unique_slug(max_length=3) }} {% endfor %}")
+            .unwrap(),
+        "abc a-2 a-3 a-4 a-5 a-6 a-7 a-8 a-9 "
+    );
+    let err =
+        render("{% for i in range(10) %}{{ 'abc' | unique_slug(max_length=3) }} {% endfor %}")
+            .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(err.to_string().contains("max_length=3"), "{err}");
+
+    let err = render("{% for i in range(2) %}{{ 'a' | unique_slug(max_length=1) }} {% endfor %}")
+        .unwrap_err();
+    assert!(err.to_string().contains("max_length=1"), "{err}");
+    let err = render("{{ 'a' | unique_slug(max_length=0) }}").unwrap_err();
+    assert!(err.to_string().contains("max_length=0"), "{err}");
+}
+
+#[test]
+fn empty_unique_slugs_get_a_name() {
+    let mut env = Environment::new();
+    env.add_filter("unique_slug", unique_slug);
+    assert_eq!(
+        env.render_str(
+            "{{ '!!!' | unique_slug }} {{ '' | unique_slug }} {{ '?' | unique_slug(max_length=3) }}",
+            ()
+        )
+        .unwrap(),
+        "section section-2 sec"
+    );
+}
+
+fn options() -> impl Strategy<Value = (&'static str, Option<usize>, bool)> {
+    (
+        prop::sample::select(vec!["-", "_", ".", "", "--"]),
+        prop::option::of(0usize..40),
+        any::<bool>(),
+    )
+}
+
+proptest! {
+    #[test]
+    fn slugify_is_idempotent(value in "\\PC*", (separator, max_length, lowercase) in options()) {
+        let options = SlugOptions { separator, max_length, lowercase };
+        let once = slug(&value, &options);
+        prop_assert_eq!(slug(&once, &options), once);
+    }
+
+    #[test]
+    fn slugs_are_ascii_and_bounded(value in "\\PC*", (separator, max_length, lowercase) in options()) {
+        let options = SlugOptions { separator, max_length, lowercase };
+        let rv = slug(&value, &options);
+        prop_assert!(rv.chars().all(|c| c.is_ascii_alphanumeric() || separator.contains(c)));
+        prop_assert!(max_length.is_none_or(|max_length| rv.len() <= max_length));
+        if !separator.is_empty() {
+            prop_assert!(!rv.starts_with(separator) && !rv.ends_with(separator));
+            prop_assert!(!rv.contains(&separator.repeat(2)));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use minijinja::context;
+use minijinja::value::Value;
+use minijinja_exploration::commands::svg;
+use minijinja_exploration::geometry::{Path, Polygon};
+use minijinja_exploration::point::Point;
+use std::fs;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/svg");
+
+#[test]
+fn renders_plan_with_escaped_labels() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("plan.svg");
+    let args = [
+        format!("{FIXTURES}/plan.json"),
+        "--output".to_string(),
+        output.to_str().unwrap().to_string(),
+    ];
+    svg::run(&args).unwrap();
+    let rendered = fs::read_to_string(&output).unwrap();
+    assert_eq!(
+        rendered,
+        fs::read_to_string(format!("{FIXTURES}/plan.svg")).unwrap()
+    );
+    assert!(rendered.contains("<title>Plot &lt;A&gt; &amp; B</title>"));
+    assert!(rendered.contains(r#"data-label="Room &quot;1&quot;""#));
+}
+
+#[test]
+fn svg_filters() {
+    let env = minijinja_exploration::svg::environment();
+    let square = Polygon(vec![
+        Point(0.0, 0.0, 0.0),
+        Point(2.0, 0.0, 0.0),
+        Point(2.0, 1.5, 0.0),
+    ]);
+    let route = Path(vec![Point(-1.0, 0.0, 0.0), Point(3.0, 4.0, 9.0)]);
+    let ctx = context! {
+        square => Value::from_object(square),
+        route => Value::from_object(route),
+    };
+    let render = |source: &str| env.render_str(source, ctx.clone());
+
+    assert_eq!(
+        render("{{ square | to_svg_path }}").unwrap(),
+        "M 0 0 L 2 0 L 2 1.5 Z"
+    );
+    assert_eq!(render("{{ route | to_svg_path }}").unwrap(), "M -1 0 L 3 4");
+    assert_eq!(
+        render("{{ [[0, 0, 0], [1, 1, 1]] | to_svg_path }}").unwrap(),
+        "M 0 0 L 1 1"
+    );
+    assert_eq!(
+        render("{{ [square, route] | viewbox }}").unwrap(),
+        "-1 0 4 4"
+    );
+    assert_eq!(
+        render("{{ square.bbox() | viewbox(padding=0.5) }}").unwrap(),
+        "-0.5 -0.5 3 2.5"
+    );
+
+    let err = render("{{ 42 | to_svg_path }}").unwrap_err();
+    assert!(err.to_string().contains("got number"), "{err}");
+    let err = render("{{ [] | viewbox }}").unwrap_err();
+    assert!(err.to_string().contains("at least one point"), "{err}");
+    let err = render("{{ square | viewbox(margin=1) }}").unwrap_err();
+    assert_eq!(err.kind(), minijinja::ErrorKind::TooManyArguments);
+    assert!(
+        err.to_string().contains("viewbox: unknown field `margin`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn svg_templates_are_auto_escaped() {
+    use minijinja::AutoEscape;
+    use minijinja_exploration::svg::auto_escape;
+    assert_eq!(auto_escape("plot.svg"), AutoEscape::Html);
+    assert_eq!(auto_escape("plot.svg.j2"), AutoEscape::Html);
+    assert_eq!(auto_escape("notes.txt"), AutoEscape::None);
+}
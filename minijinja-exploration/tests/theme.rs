@@ -0,0 +1,137 @@
+use minijinja::{Environment, ErrorKind, context};
+use minijinja_exploration::theme::{Origin, ThemeLoader};
+use std::fs;
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/theme");
+
+fn loader() -> ThemeLoader {
+    ThemeLoader::new()
+        .with_layer("project", format!("{FIXTURES}/project"))
+        .and_then(|loader| loader.with_layer("acme", format!("{FIXTURES}/acme")))
+        .and_then(|loader| loader.with_layer("defaults", format!("{FIXTURES}/defaults")))
+        .unwrap()
+}
+
+fn origin(layer: &str, name: &str) -> Origin {
+    Origin {
+        layer: layer.into(),
+        path: Path::new(FIXTURES).join(layer).join(name),
+    }
+}
+
+#[test]
+fn earlier_layers_override_later_ones() {
+    let loader = loader();
+    assert_eq!(
+        loader.resolve("index.html").unwrap(),
+        Some(origin("acme", "index.html"))
+    );
+    assert_eq!(
+        loader.resolve("base.html").unwrap(),
+        Some(origin("project", "base.html"))
+    );
+    assert_eq!(
+        loader.resolve("404.html").unwrap(),
+        Some(origin("defaults", "404.html"))
+    );
+    assert_eq!(loader.resolve("missing.html").unwrap(), None);
+}
+
+#[test]
+fn prefixes_reach_lower_layers() {
+    let loader = loader();
+    let mut env = Environment::new();
+    loader.install(&mut env);
+    let rendered = env
+        .get_template("index.html")
+        .unwrap()
+        .render(context! { brand => "Initech" })
+        .unwrap();
+    assert_eq!(
+        rendered,
+        "<header>ACME</header>\n<main>Welcome!</main>\n\
+         <footer>Brought to you by Initech. Powered by defaults</footer>"
+    );
+
+    // `acme:` skips the project layer but falls through to the defaults.
+    assert_eq!(
+        loader.resolve("acme:404.html").unwrap(),
+        Some(origin("defaults", "404.html"))
+    );
+    assert_eq!(
+        loader.resolve("defaults:index.html").unwrap(),
+        Some(origin("defaults", "index.html"))
+    );
+}
+
+#[test]
+fn reports_where_templates_came_from() {
+    let loader = loader();
+    let mut env = Environment::new();
+    loader.install(&mut env);
+    env.get_template("index.html")
+        .unwrap()
+        .render(context! {})
+        .unwrap();
+    assert_eq!(
+        loader.origin("index.html"),
+        Some(origin("acme", "index.html"))
+    );
+    assert_eq!(loader.origin("404.html"), None);
+    assert_eq!(
+        loader.origins(),
+        [
+            ("acme:base.html".to_string(), origin("acme", "base.html")),
+            ("base.html".to_string(), origin("project", "base.html")),
+            (
+                "defaults:base.html".to_string(),
+                origin("defaults", "base.html")
+            ),
+            ("index.html".to_string(), origin("acme", "index.html")),
+        ]
+    );
+    assert_eq!(
+        origin("acme", "index.html").to_string(),
+        format!("acme ({FIXTURES}/acme/index.html)")
+    );
+}
+
+#[test]
+fn bad_names_are_rejected() {
+    let loader = loader();
+    let err = loader.resolve("nope:base.html").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TemplateNotFound);
+    assert!(
+        err.to_string()
+            .contains("there is no template layer named `nope`"),
+        "{err}"
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+    fs::create_dir(dir.path().join("inner")).unwrap();
+    let loader = ThemeLoader::new()
+        .with_layer("inner", dir.path().join("inner"))
+        .unwrap();
+    assert_eq!(loader.resolve("../secret.txt").unwrap(), None);
+    assert_eq!(loader.resolve(".hidden").unwrap(), None);
+}
+
+#[test]
+fn layer_names_must_be_unique() {
+    let err = loader().with_layer("acme", "elsewhere").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(
+        err.to_string()
+            .contains("`acme` is used for more than one layer"),
+        "{err}"
+    );
+    for name in ["", "a:b"] {
+        let err = ThemeLoader::new().with_layer(name, "dir").unwrap_err();
+        assert!(
+            err.to_string().contains("is not a valid layer name"),
+            "{err}"
+        );
+    }
+}
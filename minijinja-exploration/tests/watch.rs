@@ -0,0 +1,153 @@
+use minijinja_exploration::commands::watch::{Site, dependencies};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn finds_static_dependencies() {
+    let deps = dependencies(
+        r#"{% extends "base.html" %}
+{% block body %}
+  {%- include ["missing.html", "partials/nav.html"] ignore missing %}
+  {% import "macros.html" as m %}
+  {% from 'forms.html' import field %}
+  {% set include = "not a tag" %}
+{% endblock %}"#,
+    );
+    assert!(!deps.dynamic);
+    assert_eq!(
+        deps.names.iter().map(String::as_str).collect::<Vec<_>>(),
+        [
+            "base.html",
+            "forms.html",
+            "macros.html",
+            "missing.html",
+            "partials/nav.html"
+        ]
+    );
+}
+
+#[test]
+fn computed_names_are_dynamic() {
+    assert!(dependencies("{% include theme ~ '/nav.html' %}").dynamic);
+    assert!(!dependencies("{{ include }}").dynamic);
+
+    let deps = dependencies(r#"{% include "a.html" ~ suffix %}"#);
+    assert!(deps.dynamic);
+    assert!(deps.names.is_empty());
+    assert!(dependencies(r#"{% extends ["a.html"] + more %}"#).dynamic);
+}
+
+#[test]
+fn rebuild_rerenders_only_affected_targets() {
+    let dir = tempfile::tempdir().unwrap();
+    let templates = dir.path().join("templates");
+    let output = dir.path().join("out");
+    fs::create_dir(&templates).unwrap();
+    fs::write(
+        templates.join("base.txt"),
+        "[{% block b %}{% endblock %}]\n",
+    )
+    .unwrap();
+    fs::write(
+        templates.join("page.txt"),
+        r#"{% extends "base.txt" %}{% block b %}{{ name }}{% endblock %}"#,
+    )
+    .unwrap();
+    fs::write(templates.join("solo.txt"), "solo {{ name }}\n").unwrap();
+    let context = dir.path().join("context.json");
+    fs::write(&context, r#"{"name": "Ada"}"#).unwrap();
+
+    let mut site = Site::new(
+        &templates,
+        &output,
+        vec![context.to_str().unwrap().to_string()],
+        vec!["page.txt".into(), "solo.txt".into()],
+    )
+    .unwrap();
+    let mut log = Vec::new();
+    assert_eq!(site.render_all(&mut log).unwrap(), 0);
+    assert_eq!(
+        fs::read_to_string(output.join("page.txt")).unwrap(),
+        "[Ada]\n"
+    );
+
+    let changed = BTreeSet::from(["base.txt".to_string()]);
+    assert_eq!(site.affected(&changed), ["page.txt"]);
+
+    // The cache is cleared, so the new base template is picked up.
+    fs::write(
+        templates.join("base.txt"),
+        "<{% block b %}{% endblock %}>\n",
+    )
+    .unwrap();
+    log.clear();
+    site.rebuild(&[templates.join("base.txt")], &mut log)
+        .unwrap();
+    let log = String::from_utf8(log).unwrap();
+    assert!(log.contains("rendered page.txt"), "{log}");
+    assert!(!log.contains("solo.txt"), "{log}");
+    assert_eq!(
+        fs::read_to_string(output.join("page.txt")).unwrap(),
+        "<Ada>\n"
+    );
+
+    // Changing the context re-renders everything.
+    fs::write(&context, r#"{"name": "Grace"}"#).unwrap();
+    site.rebuild(std::slice::from_ref(&context), &mut Vec::new())
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("solo.txt")).unwrap(),
+        "solo Grace\n"
+    );
+}
+
+#[test]
+fn context_files_replaced_by_a_rename_are_still_watched() {
+    let dir = tempfile::tempdir().unwrap();
+    let templates = dir.path().join("templates");
+    let output = dir.path().join("out");
+    fs::create_dir(&templates).unwrap();
+    fs::write(templates.join("solo.txt"), "solo {{ name }}\n").unwrap();
+    let context = dir.path().join("context.json");
+    fs::write(&context, r#"{"name": "Ada"}"#).unwrap();
+
+    let mut site = Site::new(
+        &templates,
+        &output,
+        vec![context.to_str().unwrap().to_string()],
+        vec!["solo.txt".into()],
+    )
+    .unwrap();
+    site.render_all(&mut Vec::new()).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    site.watch(&mut watcher).unwrap();
+
+    // Save the way many editors do, twice: the second save must be seen
+    // even though the first one replaced the watched file.
+    for name in ["Grace", "Linus"] {
+        let saved = dir.path().join("context.json.tmp");
+        fs::write(&saved, format!(r#"{{"name": "{name}"}}"#)).unwrap();
+        fs::rename(&saved, &context).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut changed = Vec::new();
+        while !changed
+            .iter()
+            .any(|path: &PathBuf| path.ends_with("context.json"))
+        {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let event = rx
+                .recv_timeout(left)
+                .expect("no event for the context file");
+            changed.extend(event.unwrap().paths);
+        }
+        site.rebuild(&changed, &mut Vec::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("solo.txt")).unwrap(),
+            format!("solo {name}\n")
+        );
+    }
+}